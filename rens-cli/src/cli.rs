@@ -3,10 +3,17 @@ pub mod renaming;
 /* Built-in imports */
 use std::path::PathBuf;
 /* Crate imports */
-use renaming::Mode;
+use renaming::{
+    options::{
+        path_exists, Confirmations, GitOpt, JournalOpt, OutputOpt, PathsOpt,
+        Recursion, SanitizeOpt, Selection,
+    },
+    Mode,
+};
 /* Dependencies */
 use clap::{Parser, Subcommand, ValueHint};
 use clap_verbosity_flag::Verbosity;
+use rens_common::mapping;
 
 #[derive(Debug, Parser)]
 #[command(version, about, long_about = None)]
@@ -22,12 +29,126 @@ pub struct Cli {
 pub enum Commands {
     #[command(flatten)]
     Renaming(Mode),
-    /// Generate shell completions
+    /// Rename files by editing their paths in `$VISUAL` or `$EDITOR`.
+    ///
+    /// Every line holds the number of a file and its path: edit the paths,
+    /// reorder the lines if you like, but keep the numbers and every line.
+    Edit {
+        /// Paths to the elements you want to rename.
+        #[arg(
+            required_unless_present = "from_stdin",
+            value_parser = path_exists,
+            value_hint = ValueHint::AnyPath,
+        )]
+        paths: Vec<PathBuf>,
+        #[command(flatten)]
+        confirmations: Confirmations,
+        #[command(flatten)]
+        git_opt: GitOpt,
+        #[command(flatten)]
+        journal_opt: JournalOpt,
+        #[command(flatten)]
+        output_opt: OutputOpt,
+        #[command(flatten)]
+        paths_opt: PathsOpt,
+        #[command(flatten)]
+        recursion: Recursion,
+        #[command(flatten)]
+        selection: Selection,
+    },
+    /// Rename files whose names Windows, FAT or exFAT don't accept.
+    ///
+    /// Forbidden characters (`<>:"/\|?*` and control characters) and bytes that aren't
+    /// valid UTF-8 are replaced, trailing dots and spaces removed, reserved names
+    /// (`CON`, `NUL.txt`...) get the replacement appended and names that are too long
+    /// get their stem truncated. The rules each file broke are reported.
+    Sanitize {
+        /// Paths to the elements you want to rename.
+        #[arg(
+            required_unless_present = "from_stdin",
+            value_parser = path_exists,
+            value_hint = ValueHint::AnyPath,
+        )]
+        paths: Vec<PathBuf>,
+        #[command(flatten)]
+        sanitize_opt: SanitizeOpt,
+        #[command(flatten)]
+        confirmations: Confirmations,
+        #[command(flatten)]
+        git_opt: GitOpt,
+        #[command(flatten)]
+        journal_opt: JournalOpt,
+        #[command(flatten)]
+        output_opt: OutputOpt,
+        #[command(flatten)]
+        paths_opt: PathsOpt,
+        #[command(flatten)]
+        recursion: Recursion,
+        #[command(flatten)]
+        selection: Selection,
+    },
+    /// Rename files following the `from -> to` pairs of a mapping file.
+    ///
+    /// CSV and TSV files hold a `from` and a `to` column (a `from,to` or `old,new`
+    /// header is skipped), JSON files an array of `{"from": .., "to": ..}` objects.
+    /// TSV files use the escaping of `--output tsv`, so dry runs can be edited and fed back.
+    /// Relative paths are relative to the working directory.
+    Map {
+        /// The mapping file.
+        #[arg(value_parser = path_exists, value_hint = ValueHint::FilePath)]
+        file: PathBuf,
+        /// Format of the mapping file, guessed from its extension by default.
+        ///
+        /// Note: `.json` files are read as JSON, `.tsv` and `.tab` as TSV, anything else as CSV.
+        #[arg(long, value_enum)]
+        format: Option<mapping::Format>,
+        #[command(flatten)]
+        confirmations: Confirmations,
+        #[command(flatten)]
+        journal_opt: JournalOpt,
+        #[command(flatten)]
+        output_opt: OutputOpt,
+    },
+    /// Rename files following a recipe saved with `--save-recipe`.
+    ///
+    /// Recipes are TOML files listing `[[step]]` tables (`mode`, `pattern`, `with`,
+    /// `case_insensitive` and `occurence`), applied in order, along with the renaming options
+    /// (`target`, `[recursion]`, `[confirmations]`, `[git]`...).
+    Run {
+        /// The recipe file.
+        #[arg(value_parser = path_exists, value_hint = ValueHint::FilePath)]
+        recipe: PathBuf,
+        /// Paths to the elements you want to rename.
+        #[arg(
+            required_unless_present = "from_stdin",
+            value_parser = path_exists,
+            value_hint = ValueHint::AnyPath,
+        )]
+        paths: Vec<PathBuf>,
+        /// Print the renames instead of performing them.
+        #[arg(long)]
+        dry_run: bool,
+        #[command(flatten)]
+        journal_opt: JournalOpt,
+        #[command(flatten)]
+        paths_opt: PathsOpt,
+    },
+    /// Revert the renames recorded in a journal.
+    Undo {
+        /// The journal written using `--journal`.
+        #[arg(value_hint = ValueHint::FilePath)]
+        journal: PathBuf,
+        #[command(flatten)]
+        confirmations: Confirmations,
+        #[command(flatten)]
+        output_opt: OutputOpt,
+    },
+    /// Generate shell completions.
     Completions {
-        /// The shell to generate the completions for
+        /// The shell to generate the completions for.
         shell: clap_complete_command::Shell,
     },
-    /// Generate man page
+    /// Generate man page.
     Man {
         /// The dir path to generate man-pages to.
         ///
@@ -37,13 +158,75 @@ pub enum Commands {
     },
 }
 
+impl Commands {
+    /// Whether configuration files provide defaults for the command.
+    ///
+    /// Note: Recipes already hold every option, the other commands don't
+    /// touch any file.
+    pub const fn is_configurable(&self) -> bool {
+        !matches!(
+            *self,
+            Self::Run { .. } | Self::Completions { .. } | Self::Man { .. }
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use clap::CommandFactory;
+    use clap::CommandFactory as _;
 
     #[test]
     fn verify_conformity() {
         Cli::command().debug_assert();
     }
+
+    #[test]
+    fn test_map_format() {
+        let parsed = |args: &[&str]| {
+            let cli = Cli::parse_from(
+                ["rens-cli", "map", "Cargo.toml"].iter().chain(args),
+            );
+            if let Commands::Map { format, .. } = cli.command {
+                Some(format)
+            } else {
+                None
+            }
+        };
+        assert_eq!(parsed(&[]), Some(None));
+        assert_eq!(
+            parsed(&["--format", "tsv"]),
+            Some(Some(mapping::Format::Tsv))
+        );
+        assert_eq!(
+            parsed(&["--format=JSON"]),
+            Some(Some(mapping::Format::Json))
+        );
+        Cli::try_parse_from(["rens-cli", "map", "Cargo.toml", "--format=xml"])
+            .unwrap_err();
+    }
+
+    #[test]
+    fn test_run_paths() {
+        Cli::try_parse_from(["rens-cli", "run", "Cargo.toml"]).unwrap_err();
+        let cli =
+            Cli::parse_from(["rens-cli", "run", "Cargo.toml", "--from-stdin"]);
+        assert!(matches!(
+            cli.command,
+            Commands::Run { paths, paths_opt, .. }
+                if paths.is_empty() && paths_opt.from_stdin
+        ));
+    }
+
+    #[test]
+    fn test_is_configurable() {
+        let configurable =
+            |args: &[&str]| Cli::parse_from(args).command.is_configurable();
+        assert!(configurable(&["rens-cli", "string", "a", "b", "."]));
+        assert!(configurable(&["rens-cli", "sanitize", "."]));
+        assert!(configurable(&["rens-cli", "undo", "journal.json"]));
+        assert!(!configurable(&["rens-cli", "completions", "bash"]));
+        assert!(!configurable(&["rens-cli", "man", "man"]));
+        assert!(!configurable(&["rens-cli", "run", "Cargo.toml", "."]));
+    }
 }
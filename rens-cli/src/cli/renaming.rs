@@ -1,11 +1,11 @@
 /* Modules */
 pub mod options;
 /* Crate imports */
-use self::options::{Options, PatternOpt};
+use self::options::{Form, Options, PatternOpt};
 /* Dependencies */
 use clap::Subcommand;
-use regex::{Regex, RegexBuilder};
-use rens_common::{SedPattern, Strategy};
+use regex::bytes::Regex;
+use rens_common::{template, SedPattern, Template};
 
 #[derive(Debug, Subcommand)]
 pub enum Mode {
@@ -14,8 +14,9 @@ pub enum Mode {
     String {
         /// The pattern you're looking to rename.
         pattern: String,
-        /// The string you with to replace it with.
-        with: String,
+        /// The string you with to replace it with, see --help for placeholders.
+        #[arg(long_help = template::SYNTAX)]
+        with: Template,
         #[command(flatten)]
         pattern_opt: PatternOpt,
         #[command(flatten)]
@@ -28,8 +29,9 @@ pub enum Mode {
         ///
         /// Must comply with the `regex` crate syntax.
         pattern: Regex,
-        /// The string you with to replace it with.
-        with: String,
+        /// The string you with to replace it with, see --help for placeholders.
+        #[arg(long_help = template::SYNTAX)]
+        with: Template,
         #[command(flatten)]
         pattern_opt: PatternOpt,
         #[command(flatten)]
@@ -39,77 +41,44 @@ pub enum Mode {
     Sed {
         /// The sed pattern used to rename.
         /// Follows the pattern /regex/string/options.
-        /// [supported options: g, i, I, x, U, <number>]
+        /// [supported options: g, i, I, x, U, <number>].
         ///
         /// Notes:
         ///  - `g` flag is enabled by default (pass any number to restrict).
         ///  - You can use anything as a separator.
         ///  - The regex must comply with `regex` crate syntax.
         ///  - You can escape the separator (any other escape sequence will be kept as is).
+        ///  - The replacement supports the placeholders of the `regex` mode.
         #[arg(verbatim_doc_comment)]
         sed_pattern: SedPattern,
         #[command(flatten)]
         options: Options,
     },
+    /// Normalize the unicode form of names.
+    ///
+    /// Names that look the same may be encoded differently, e.g. files synced
+    /// from macOS have decomposed accents and don't match composed patterns.
+    #[command(alias = "norm")]
+    Normalize {
+        /// The form to normalize names to.
+        #[arg(value_enum)]
+        form: Form,
+        #[command(flatten)]
+        options: Options,
+    },
 }
 
-impl Mode {
-    pub fn get_strategy_and_options(self) -> (Strategy, Options) {
-        match self {
-            Self::Regex {
-                mut pattern,
-                with,
-                pattern_opt,
-                options,
-            } => {
-                let limit = pattern_opt.occurence.map_or(0, usize::from);
-                if pattern_opt.case_insensitive {
-                    pattern = to_regex_case_insensitive(&pattern);
-                }
-
-                (Strategy::new(pattern, with, limit), options)
-            },
-            Self::String {
-                pattern,
-                with,
-                pattern_opt,
-                options,
-            } => {
-                let limit = pattern_opt.occurence.map_or(0, usize::from);
-                // safety guarenteed by [`regex::escape`]
-                #[allow(clippy::expect_used)]
-                let mut regex_pattern = Regex::new(&regex::escape(&pattern))
-                    .expect("Unable to build regex.");
-
-                if pattern_opt.case_insensitive {
-                    regex_pattern = to_regex_case_insensitive(&regex_pattern);
-                }
-
-                (Strategy::new(regex_pattern, with, limit), options)
-            },
-            Self::Sed {
-                sed_pattern,
-                options,
-            } => {
-                let (pattern, with, limit) = sed_pattern.export();
-                (Strategy::new(pattern, with, limit), options)
-            },
-        }
-    }
-}
-
-#[allow(clippy::expect_used)]
-fn to_regex_case_insensitive(regex: &Regex) -> Regex {
-    RegexBuilder::new(regex.as_str())
-        .case_insensitive(true)
-        .build()
-        .expect("Failed to build case insensitive Regex.")
+/// Regex matching `pattern` literally.
+pub fn escaped(pattern: &str) -> Regex {
+    // safety guarenteed by [`regex::escape`]
+    #[allow(clippy::expect_used)]
+    Regex::new(&regex::escape(pattern)).expect("Unable to build regex.")
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use clap::{CommandFactory, Parser};
+    use clap::{CommandFactory as _, Parser};
 
     #[derive(Debug, Parser)]
     struct TestParser {
@@ -1,71 +1,130 @@
 /* Modules */
 mod confirmations;
-mod git;
+mod journal;
+mod normalization;
+mod output;
 mod paths;
-mod pattern;
-mod recursion;
+mod sanitize;
+mod sequence;
 /* Built-in imports */
-use std::{io, path::PathBuf};
+use std::path::PathBuf;
 /* Dependencies */
 use clap::{Args, ValueHint};
-use rens_common::RenameTarget;
+use rens_common::{RenameTarget, SedPattern, COMPOUND_EXTENSIONS};
+use serde::{Deserialize, Serialize};
 /* Re-exports */
 pub use self::{
-    confirmations::{ConfirmOption, Confirmations, OverrideOption},
-    git::Options as GitOpt,
+    confirmations::{
+        CollisionOption, ConfirmOption, Confirmations, OverrideOption,
+    },
+    journal::Options as JournalOpt,
+    normalization::Form,
+    output::{Options as OutputOpt, OutputFormat},
     paths::Options as PathsOpt,
-    pattern::Options as PatternOpt,
-    recursion::Recursion,
+    sanitize::Options as SanitizeOpt,
+    sequence::Options as SequenceOpt,
+};
+pub use rens_common::options::{
+    git::Options as GitOpt, path_exists, pattern::Options as PatternOpt,
+    recursion::Recursion, selection::Selection,
 };
 
-#[derive(Debug, Args)]
+#[derive(Debug, Default, Clone, Args, Serialize, Deserialize)]
+#[serde(default)]
 #[command(next_display_order = 0)]
 pub struct Options {
-    /// Wether to rename the file stem, extension or both.
+    /// Wether to rename the file stem, extension(s), both or the whole path.
     ///
-    /// Note: filename = <stem>.<extension>
+    /// Note: filename = <stem>.<extension>, `extensions` being the last
+    /// extension along with a compound one right before it (e.g. `tar.gz.gpg`
+    /// of `db.v2.tar.gz.gpg`), other dots belong to the stem.
+    /// `path` is relative to the current directory (e.g. `2023/01/a.jpg`),
+    /// files are moved and missing directories created, files outside of the
+    /// current directory are refused.
     #[arg(long, short, default_value = "both", value_enum)]
     pub target: RenameTarget,
 
+    /// Extensions kept whole when targeting the stem or extension,
+    /// replacing the defaults.
+    #[arg(
+        long = "compound-ext",
+        value_name = "ext",
+        value_delimiter = ',',
+        default_values = COMPOUND_EXTENSIONS,
+    )]
+    #[serde(default = "compound_extensions")]
+    pub compound_extensions: Vec<String>,
+
+    /// Sed pattern applied after the main one, can be repeated.
+    ///
+    /// Expressions are applied in order, each to the result of the previous one
+    /// (use -vvv to log the intermediate names).
+    #[arg(long = "expression", short = 'e', value_name = "sed pattern")]
+    // Saved as recipe steps
+    #[serde(skip)]
+    pub expressions: Vec<SedPattern>,
+
+    /// Unicode form the names are normalized to, after the replacements.
+    #[arg(long, value_enum, value_name = "form")]
+    // Saved as a recipe step
+    #[serde(skip)]
+    pub normalize: Option<Form>,
+
+    /// Save the pattern and options into a TOML recipe, for `rens run`.
+    ///
+    /// Note: The renaming still happens, combine with --dry-run to only save the recipe.
+    #[arg(long, value_name = "recipe", value_hint = ValueHint::FilePath)]
+    #[serde(skip)]
+    pub save_recipe: Option<PathBuf>,
+
     /// Paths to the elements you want to rename.
     #[arg(
-        required = true,
+        required_unless_present = "from_stdin",
         value_parser = path_exists,
         value_hint = ValueHint::AnyPath,
     )]
+    #[serde(skip)]
     pub paths: Vec<PathBuf>,
 
     #[command(flatten)]
     pub confirmations: Confirmations,
 
     #[command(flatten)]
+    #[serde(rename = "git")]
     pub git_opt: GitOpt,
 
     #[command(flatten)]
+    // Given to each run
+    #[serde(skip)]
+    pub journal_opt: JournalOpt,
+
+    #[command(flatten)]
+    #[serde(rename = "output")]
+    pub output_opt: OutputOpt,
+
+    #[command(flatten)]
+    #[serde(rename = "paths")]
     pub paths_opt: PathsOpt,
 
     #[command(flatten)]
     pub recursion: Recursion,
+
+    #[command(flatten)]
+    pub selection: Selection,
+
+    #[command(flatten)]
+    #[serde(rename = "sequence")]
+    pub sequence_opt: SequenceOpt,
 }
 
-fn path_exists(input: &str) -> io::Result<PathBuf> {
-    let path: PathBuf = input.into();
-    if path.exists() {
-        // simply ensure the path is canonicalizable
-        dunce::canonicalize(&path)?;
-        Ok(path)
-    } else {
-        Err(io::Error::new(
-            io::ErrorKind::NotFound,
-            format!("No such file or directory: '{input}'."),
-        ))
-    }
+fn compound_extensions() -> Vec<String> {
+    COMPOUND_EXTENSIONS.map(str::to_owned).to_vec()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use clap::{CommandFactory, Parser};
+    use clap::{CommandFactory as _, Parser};
 
     #[derive(Debug, Parser)]
     struct TestParser {
@@ -78,6 +137,32 @@ mod tests {
         TestParser::command().debug_assert();
     }
 
+    #[test]
+    fn test_expressions() {
+        assert!(TestParser::parse_from(["rens-cli", "."])
+            .options
+            .expressions
+            .is_empty());
+
+        let args = TestParser::parse_from([
+            "rens-cli", "-e", "/a/b/", "-e=|c|d|", ".",
+        ]);
+        assert_eq!(args.options.expressions.len(), 2);
+
+        TestParser::try_parse_from(["rens-cli", "-e", "/a(/b/", "."])
+            .unwrap_err();
+    }
+
+    #[test]
+    fn test_paths() {
+        TestParser::try_parse_from(["rens-cli"]).unwrap_err();
+        TestParser::try_parse_from(["rens-cli", "does/not/exist"]).unwrap_err();
+        assert!(TestParser::parse_from(["rens-cli", "--from-stdin"])
+            .options
+            .paths
+            .is_empty());
+    }
+
     #[test]
     fn test_target() {
         TestParser::try_parse_from(["rens-cli", "--target", "."]).unwrap_err();
@@ -102,5 +187,39 @@ mod tests {
                 .target,
             RenameTarget::Stem
         );
+
+        assert_eq!(
+            TestParser::parse_from(["rens-cli", "--target=extensions", "."])
+                .options
+                .target,
+            RenameTarget::Extensions
+        );
+
+        assert_eq!(
+            TestParser::parse_from(["rens-cli", "--target=path", "."])
+                .options
+                .target,
+            RenameTarget::Path
+        );
+    }
+
+    #[test]
+    fn test_compound_extensions() {
+        assert_eq!(
+            TestParser::parse_from(["rens-cli", "."])
+                .options
+                .compound_extensions,
+            compound_extensions()
+        );
+        assert_eq!(
+            TestParser::parse_from([
+                "rens-cli",
+                "--compound-ext=tar.gz,min.js",
+                "."
+            ])
+            .options
+            .compound_extensions,
+            ["tar.gz", "min.js"]
+        );
     }
 }
@@ -1,7 +1,9 @@
 /* Dependencies */
 use clap::{Args, ValueEnum};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Args)]
+#[derive(Debug, Default, Clone, Args, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 #[command(next_help_heading = "Confirmation Options")]
 pub struct Confirmations {
     /// Behavior when a renamed file already exists.
@@ -25,10 +27,18 @@ pub struct Confirmations {
         num_args = 0..=1,
     )]
     pub confirm: ConfirmOption,
+
+    /// Behavior when several files would be renamed to the same path.
+    #[arg(long, value_enum, default_value = "abort", require_equals = true)]
+    pub on_collision: CollisionOption,
 }
 
-#[derive(Debug, Clone, ValueEnum, PartialEq, Eq)]
+#[derive(
+    Debug, Default, Clone, ValueEnum, PartialEq, Eq, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
 pub enum OverrideOption {
+    #[default]
     #[clap(help = "Ask for every change.")]
     Ask,
     #[clap(help = "Always allow.")]
@@ -37,8 +47,12 @@ pub enum OverrideOption {
     Deny,
 }
 
-#[derive(Debug, Clone, ValueEnum, PartialEq, Eq)]
+#[derive(
+    Debug, Default, Clone, ValueEnum, PartialEq, Eq, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
 pub enum ConfirmOption {
+    #[default]
     #[clap(help = "Ask for every change.")]
     Each,
     #[clap(help = "Ask once after showing every change.")]
@@ -47,10 +61,24 @@ pub enum ConfirmOption {
     Never,
 }
 
+#[derive(
+    Debug, Default, Clone, ValueEnum, PartialEq, Eq, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum CollisionOption {
+    #[default]
+    #[clap(help = "Don't rename anything.")]
+    Abort,
+    #[clap(help = "Don't rename the colliding files.")]
+    Skip,
+    #[clap(help = "Append ` (1)`, ` (2)`... to the colliding names.")]
+    Suffix,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use clap::{CommandFactory, Parser};
+    use clap::{CommandFactory as _, Parser};
 
     #[derive(Debug, Parser)]
     struct TestParser {
@@ -68,6 +96,7 @@ mod tests {
         let args = TestParser::parse_from::<[_; 0], &str>([]);
         assert_eq!(args.confirmations.allow_override, OverrideOption::Ask);
         assert_eq!(args.confirmations.confirm, ConfirmOption::Each);
+        assert_eq!(args.confirmations.on_collision, CollisionOption::Abort);
     }
 
     #[test]
@@ -131,4 +160,24 @@ mod tests {
             ConfirmOption::Once
         );
     }
+
+    #[test]
+    fn test_on_collision() {
+        // should fail if no value provided
+        TestParser::try_parse_from(["rens-cli", "--on-collision"]).unwrap_err();
+
+        assert_eq!(
+            TestParser::parse_from(["rens-cli", "--on-collision=skip"])
+                .confirmations
+                .on_collision,
+            CollisionOption::Skip
+        );
+
+        assert_eq!(
+            TestParser::parse_from(["rens-cli", "--on-collision=suffix"])
+                .confirmations
+                .on_collision,
+            CollisionOption::Suffix
+        );
+    }
 }
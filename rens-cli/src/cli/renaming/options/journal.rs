@@ -0,0 +1,50 @@
+/* Built-in imports */
+use std::path::PathBuf;
+/* Dependencies */
+use clap::{Args, ValueHint};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Clone, Args, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+#[group(id = "journal_options")]
+#[command(next_help_heading = "Journal Options")]
+pub struct Options {
+    /// Record every executed rename in a journal, revertable using `undo`.
+    ///
+    /// Note: `.json` files are written as JSON, anything else as TSV,
+    /// existing journals aren't overwritten.
+    #[arg(long, value_name = "path", value_hint = ValueHint::FilePath)]
+    pub journal: Option<PathBuf>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{CommandFactory as _, Parser};
+
+    #[derive(Debug, Parser)]
+    struct TestParser {
+        #[command(flatten)]
+        pub options: Options,
+    }
+
+    #[test]
+    fn verify_conformity() {
+        TestParser::command().debug_assert();
+    }
+
+    #[test]
+    fn test_journal() {
+        assert_eq!(TestParser::parse_from(["rens-cli"]).options.journal, None);
+
+        // should fail if no value provided
+        TestParser::try_parse_from(["rens-cli", "--journal"]).unwrap_err();
+
+        assert_eq!(
+            TestParser::parse_from(["rens-cli", "--journal", "rens.json"])
+                .options
+                .journal,
+            Some(PathBuf::from("rens.json"))
+        );
+    }
+}
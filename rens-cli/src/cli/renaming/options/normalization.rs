@@ -0,0 +1,31 @@
+/* Dependencies */
+use clap::ValueEnum;
+use rens_common::template::Transform;
+use serde::{Deserialize, Serialize};
+
+/// Unicode form file names are normalized to.
+#[derive(
+    Debug, Clone, Copy, ValueEnum, PartialEq, Eq, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Form {
+    #[clap(help = "Composed accents (`é`), as most systems write them.")]
+    Nfc,
+    #[clap(help = "Decomposed accents (`e` + `´`), as macOS writes them.")]
+    Nfd,
+    #[clap(
+        help = "ASCII transliteration (`é` → `e`, `ß` → `ss`, `ж` → `zh`)."
+    )]
+    Ascii,
+}
+
+impl From<Form> for Transform {
+    #[inline]
+    fn from(form: Form) -> Self {
+        match form {
+            Form::Nfc => Self::Nfc,
+            Form::Nfd => Self::Nfd,
+            Form::Ascii => Self::Ascii,
+        }
+    }
+}
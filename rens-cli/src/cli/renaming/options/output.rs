@@ -0,0 +1,90 @@
+/* Dependencies */
+use clap::{ArgAction, Args, ValueEnum};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Clone, Args, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+#[group(id = "output_options")]
+#[command(next_help_heading = "Output Options")]
+pub struct Options {
+    /// Print the renames that would be done, without touching the disk.
+    #[arg(
+        long,
+        default_value_t = false,
+        action = ArgAction::SetTrue
+    )]
+    // Recipes are meant to be run
+    #[serde(skip)]
+    pub dry_run: bool,
+
+    /// Format used to print the renames.
+    ///
+    /// Note: ignored when confirming each rename.
+    #[arg(long, value_enum, default_value = "plain")]
+    pub output: OutputFormat,
+}
+
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    ValueEnum,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    #[default]
+    #[clap(help = "One `<path> -> <renamed path>` line per rename.")]
+    Plain,
+    #[clap(help = "Array of `from`, `to` and `conflict` objects.")]
+    Json,
+    #[clap(help = "`from`, `to` and `conflict` columns, with a header.")]
+    Tsv,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{CommandFactory as _, Parser};
+
+    #[derive(Debug, Parser)]
+    struct TestParser {
+        #[command(flatten)]
+        pub options: Options,
+    }
+
+    #[test]
+    fn verify_conformity() {
+        TestParser::command().debug_assert();
+    }
+
+    #[test]
+    fn test_default_values() {
+        let args = TestParser::parse_from::<[_; 0], &str>([]);
+        assert!(!args.options.dry_run);
+        assert_eq!(args.options.output, OutputFormat::Plain);
+    }
+
+    #[test]
+    fn test_output() {
+        TestParser::try_parse_from(["rens-cli", "--output=xml"]).unwrap_err();
+
+        assert_eq!(
+            TestParser::parse_from(["rens-cli", "--output=json"])
+                .options
+                .output,
+            OutputFormat::Json
+        );
+
+        assert_eq!(
+            TestParser::parse_from(["rens-cli", "--output", "tsv"])
+                .options
+                .output,
+            OutputFormat::Tsv
+        );
+    }
+}
@@ -1,7 +1,9 @@
 /* Dependencies */
 use clap::{ArgAction, Args};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Args)]
+#[derive(Debug, Default, Clone, Args, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 #[group(id = "path_options")]
 #[command(next_help_heading = "Path Options")]
 pub struct Options {
@@ -12,12 +14,33 @@ pub struct Options {
         action = ArgAction::SetTrue
     )]
     pub canonicalize_paths: bool,
+
+    /// Read the paths to rename from stdin, one per line, after the given ones.
+    ///
+    /// Note: answers to confirmations are then read from the terminal.
+    #[arg(long, default_value_t = false, action = ArgAction::SetTrue)]
+    // Given when running, not a default
+    #[serde(skip)]
+    pub from_stdin: bool,
+
+    /// Paths read from stdin are separated by NUL characters instead of newlines.
+    ///
+    /// Note: as output by `find -print0`, `fd -0` or `git ls-files -z`.
+    #[arg(
+        long,
+        short = '0',
+        default_value_t = false,
+        requires = "from_stdin",
+        action = ArgAction::SetTrue
+    )]
+    #[serde(skip)]
+    pub null: bool,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use clap::{CommandFactory, Parser};
+    use clap::{CommandFactory as _, Parser};
 
     #[derive(Debug, Parser)]
     struct TestParser {
@@ -29,4 +52,18 @@ mod tests {
     fn verify_conformity() {
         TestParser::command().debug_assert();
     }
+
+    #[test]
+    fn test_from_stdin() {
+        let defaults = TestParser::parse_from::<[_; 0], &str>([]);
+        assert!(!defaults.options.from_stdin);
+        assert!(!defaults.options.null);
+
+        let args = TestParser::parse_from(["rens-cli", "--from-stdin", "-0"]);
+        assert!(args.options.from_stdin);
+        assert!(args.options.null);
+
+        // NUL separators only make sense for stdin
+        TestParser::try_parse_from(["rens-cli", "--null"]).unwrap_err();
+    }
 }
@@ -0,0 +1,69 @@
+/* Dependencies */
+use clap::Args;
+use rens_common::sanitize::{self, Policy, MAX_LENGTH};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Args, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+#[group(id = "sanitize_options")]
+#[command(next_help_heading = "Sanitize Options")]
+pub struct Options {
+    /// Replaces forbidden characters and bytes that aren't valid UTF-8.
+    ///
+    /// Note: Also appended to reserved names, `_` being used if empty.
+    #[arg(long, default_value = "_", value_name = "string")]
+    pub replacement: String,
+
+    /// Longest name allowed, in UTF-16 units like Windows counts them.
+    #[arg(long, default_value_t = MAX_LENGTH, value_name = "number")]
+    pub max_length: usize,
+}
+
+impl Default for Options {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            replacement: "_".to_owned(),
+            max_length: MAX_LENGTH,
+        }
+    }
+}
+
+impl Options {
+    pub fn policy(&self) -> Result<Policy, sanitize::Error> {
+        Policy::new(&self.replacement, self.max_length)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{CommandFactory as _, Parser};
+
+    #[derive(Debug, Parser)]
+    struct TestParser {
+        #[command(flatten)]
+        pub options: Options,
+    }
+
+    #[test]
+    fn sanitize_options_are_valid() {
+        TestParser::command().debug_assert();
+    }
+
+    #[test]
+    fn test_policy() {
+        let policy = |args: &[&str]| {
+            let mut all = vec!["rens-cli"];
+            all.extend(args);
+            TestParser::parse_from(all).options.policy()
+        };
+        assert_eq!(policy(&[]), Ok(Policy::default()));
+        assert_eq!(
+            policy(&["--replacement=-", "--max-length=64"]),
+            Policy::new("-", 64)
+        );
+        policy(&["--replacement=?"]).unwrap_err();
+        policy(&["--max-length=0"]).unwrap_err();
+    }
+}
@@ -0,0 +1,75 @@
+/* Dependencies */
+use clap::Args;
+use rens_common::template::SortKey;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Args, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+#[group(id = "sequence_options")]
+#[command(next_help_heading = "Sequence Options")]
+pub struct Options {
+    /// Number given to the first file by counter placeholders.
+    #[arg(long, default_value_t = 1, value_name = "number")]
+    pub start: u64,
+
+    /// Increment between two consecutive files.
+    #[arg(long, default_value_t = 1, value_name = "number")]
+    pub step: u64,
+
+    /// Order in which files get numbered.
+    ///
+    /// Note: `natural` sorts by name, comparing numbers by value.
+    #[arg(long, default_value = "name", value_enum)]
+    pub sort: SortKey,
+}
+
+impl Default for Options {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            start: 1,
+            step: 1,
+            sort: SortKey::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{CommandFactory as _, Parser};
+
+    #[derive(Debug, Parser)]
+    struct TestParser {
+        #[command(flatten)]
+        pub options: Options,
+    }
+
+    #[test]
+    fn verify_conformity() {
+        TestParser::command().debug_assert();
+    }
+
+    #[test]
+    fn test_defaults() {
+        let options = TestParser::parse_from(["rens-cli"]).options;
+        assert_eq!(options.start, 1);
+        assert_eq!(options.step, 1);
+        assert_eq!(options.sort, SortKey::Name);
+    }
+
+    #[test]
+    fn test_sequence() {
+        let options = TestParser::parse_from([
+            "rens-cli", "--start", "10", "--step", "5", "--sort", "natural",
+        ])
+        .options;
+        assert_eq!(options.start, 10);
+        assert_eq!(options.step, 5);
+        assert_eq!(options.sort, SortKey::Natural);
+
+        TestParser::try_parse_from(["rens-cli", "--sort", "random"])
+            .unwrap_err();
+        TestParser::try_parse_from(["rens-cli", "--start", "-1"]).unwrap_err();
+    }
+}
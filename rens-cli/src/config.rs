@@ -0,0 +1,411 @@
+//! Default option values read from `rens.toml` files.
+//!
+//! `$XDG_CONFIG_HOME/rens/config.toml` is read first, then every `rens.toml`
+//! from the root down to the current directory, the nearest file winning.
+//! Flags given on the command line override them all:
+//!
+//! ```toml
+//! [confirmations]
+//! confirm = "once"
+//!
+//! [git]
+//! ignore = true
+//!
+//! [sanitize]
+//! replacement = "-"
+//! ```
+
+/* Built-in imports */
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+/* Crate imports */
+use crate::cli::{
+    renaming::{
+        options::{
+            Confirmations, GitOpt, Options, PathsOpt, PatternOpt, Recursion,
+            SanitizeOpt,
+        },
+        Mode,
+    },
+    Commands,
+};
+/* Dependencies */
+use clap::{parser::ValueSource, ArgMatches};
+use log::debug;
+use rens_common::os_bytes;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use toml::{de, ser, Table, Value};
+
+const FILE_NAME: &str = "rens.toml";
+
+/// Path of the user's configuration, `$XDG_CONFIG_HOME/rens/config.toml`.
+#[inline]
+pub fn user_config() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| {
+            env::var_os("HOME").map(|home| Path::new(&home).join(".config"))
+        })
+        .map(|config_dir| config_dir.join("rens").join("config.toml"))
+}
+
+/// Sections a configuration file can hold, to validate it.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[allow(dead_code)]
+struct Sections {
+    confirmations: Option<Confirmations>,
+    git: Option<GitOpt>,
+    paths: Option<PathsOpt>,
+    pattern: Option<PatternOpt>,
+    recursion: Option<Recursion>,
+    sanitize: Option<SanitizeOpt>,
+}
+
+#[derive(Debug, Default)]
+pub struct Config {
+    sections: Table,
+}
+
+impl Config {
+    /// Merges the `user` configuration with the `rens.toml` files found
+    /// walking up from `dir`.
+    #[inline]
+    pub fn discover(user: Option<PathBuf>, dir: &Path) -> Result<Self, Error> {
+        let mut ancestors = dir
+            .ancestors()
+            .map(|ancestor| ancestor.join(FILE_NAME))
+            .collect::<Vec<_>>();
+        ancestors.reverse();
+
+        user.into_iter()
+            .chain(ancestors)
+            .filter(|path| path.is_file())
+            .try_fold(Self::default(), |mut config, path| {
+                config.merge(&path)?;
+                debug!("Loaded config from {}", os_bytes::escape_path(&path));
+                Ok(config)
+            })
+    }
+
+    /// Reads the file at `path`, its values overriding the current ones.
+    fn merge(&mut self, path: &Path) -> Result<(), Error> {
+        let text = fs::read_to_string(path)?;
+        let parsed = toml::from_str::<Sections>(&text)
+            .and_then(|_| toml::from_str::<Table>(&text))
+            .map_err(|err| Error::Parse(path.to_owned(), err))?;
+        for (name, section) in parsed {
+            if let (Some(current), Value::Table(values)) = (
+                self.sections
+                    .entry(name)
+                    .or_insert_with(|| Value::Table(Table::new()))
+                    .as_table_mut(),
+                section,
+            ) {
+                current.extend(values);
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies the defaults to the options of `command`, `matches` telling
+    /// which ones were given on the command line.
+    ///
+    /// Note: Recipes are left as is, they already hold every option.
+    #[inline]
+    #[allow(clippy::too_many_lines)]
+    pub fn configure(
+        &self,
+        command: Commands,
+        matches: &ArgMatches,
+    ) -> Result<Commands, Error> {
+        Ok(match command {
+            Commands::Renaming(Mode::String {
+                pattern,
+                with,
+                pattern_opt,
+                options,
+            }) => Commands::Renaming(Mode::String {
+                pattern,
+                with,
+                pattern_opt: self.overlay("pattern", pattern_opt, matches)?,
+                options: self.options(options, matches)?,
+            }),
+            Commands::Renaming(Mode::Regex {
+                pattern,
+                with,
+                pattern_opt,
+                options,
+            }) => Commands::Renaming(Mode::Regex {
+                pattern,
+                with,
+                pattern_opt: self.overlay("pattern", pattern_opt, matches)?,
+                options: self.options(options, matches)?,
+            }),
+            Commands::Renaming(Mode::Sed {
+                sed_pattern,
+                options,
+            }) => Commands::Renaming(Mode::Sed {
+                sed_pattern,
+                options: self.options(options, matches)?,
+            }),
+            Commands::Renaming(Mode::Normalize { form, options }) => {
+                Commands::Renaming(Mode::Normalize {
+                    form,
+                    options: self.options(options, matches)?,
+                })
+            },
+            Commands::Edit {
+                paths,
+                confirmations,
+                git_opt,
+                journal_opt,
+                output_opt,
+                paths_opt,
+                recursion,
+                selection,
+            } => Commands::Edit {
+                paths,
+                confirmations: self.overlay(
+                    "confirmations",
+                    confirmations,
+                    matches,
+                )?,
+                git_opt: self.overlay("git", git_opt, matches)?,
+                journal_opt,
+                output_opt,
+                paths_opt: self.overlay("paths", paths_opt, matches)?,
+                recursion: self.overlay("recursion", recursion, matches)?,
+                selection,
+            },
+            Commands::Sanitize {
+                paths,
+                sanitize_opt,
+                confirmations,
+                git_opt,
+                journal_opt,
+                output_opt,
+                paths_opt,
+                recursion,
+                selection,
+            } => Commands::Sanitize {
+                paths,
+                sanitize_opt: self.overlay(
+                    "sanitize",
+                    sanitize_opt,
+                    matches,
+                )?,
+                confirmations: self.overlay(
+                    "confirmations",
+                    confirmations,
+                    matches,
+                )?,
+                git_opt: self.overlay("git", git_opt, matches)?,
+                journal_opt,
+                output_opt,
+                paths_opt: self.overlay("paths", paths_opt, matches)?,
+                recursion: self.overlay("recursion", recursion, matches)?,
+                selection,
+            },
+            Commands::Map {
+                file,
+                format,
+                confirmations,
+                journal_opt,
+                output_opt,
+            } => Commands::Map {
+                file,
+                format,
+                confirmations: self.overlay(
+                    "confirmations",
+                    confirmations,
+                    matches,
+                )?,
+                journal_opt,
+                output_opt,
+            },
+            Commands::Undo {
+                journal,
+                confirmations,
+                output_opt,
+            } => Commands::Undo {
+                journal,
+                confirmations: self.overlay(
+                    "confirmations",
+                    confirmations,
+                    matches,
+                )?,
+                output_opt,
+            },
+            other @ (Commands::Run { .. }
+            | Commands::Completions { .. }
+            | Commands::Man { .. }) => other,
+        })
+    }
+
+    fn options(
+        &self,
+        options: Options,
+        matches: &ArgMatches,
+    ) -> Result<Options, Error> {
+        Ok(Options {
+            confirmations: self.overlay(
+                "confirmations",
+                options.confirmations,
+                matches,
+            )?,
+            git_opt: self.overlay("git", options.git_opt, matches)?,
+            paths_opt: self.overlay("paths", options.paths_opt, matches)?,
+            recursion: self.overlay("recursion", options.recursion, matches)?,
+            ..options
+        })
+    }
+
+    /// Replaces the values of `options` by those of the `section`,
+    /// unless given on the command line.
+    fn overlay<T>(
+        &self,
+        section: &str,
+        options: T,
+        matches: &ArgMatches,
+    ) -> Result<T, Error>
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        let Some(defaults) =
+            self.sections.get(section).and_then(Value::as_table)
+        else {
+            return Ok(options);
+        };
+        let mut values = Table::try_from(options)?;
+        for (key, value) in defaults {
+            if !given(matches, key) {
+                values.insert(key.clone(), value.clone());
+            }
+        }
+        Ok(values.try_into()?)
+    }
+}
+
+/// Whether the argument `id` was explicitly set by the user.
+fn given(matches: &ArgMatches, id: &str) -> bool {
+    matches.ids().any(|known| known == id)
+        && matches!(
+            matches.value_source(id),
+            Some(ValueSource::CommandLine | ValueSource::EnvVariable)
+        )
+}
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("{}: {1}", os_bytes::escape_path(.0))]
+    Parse(PathBuf, de::Error),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Deserialize(#[from] de::Error),
+    #[error(transparent)]
+    Serialize(#[from] ser::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{
+        renaming::options::{ConfirmOption, OverrideOption},
+        Cli,
+    };
+    use clap::{CommandFactory as _, FromArgMatches as _};
+
+    fn configured(config: &Config, args: &[&str]) -> Options {
+        let matches = Cli::command().get_matches_from(args);
+        let cli = Cli::from_arg_matches(&matches).unwrap();
+        let sub_matches = matches.subcommand().unwrap().1;
+        match config.configure(cli.command, sub_matches).unwrap() {
+            Commands::Renaming(
+                Mode::String { options, .. }
+                | Mode::Regex { options, .. }
+                | Mode::Sed { options, .. }
+                | Mode::Normalize { options, .. },
+            ) => options,
+            Commands::Edit { .. }
+            | Commands::Sanitize { .. }
+            | Commands::Map { .. }
+            | Commands::Run { .. }
+            | Commands::Undo { .. }
+            | Commands::Completions { .. }
+            | Commands::Man { .. } => Options::default(),
+        }
+    }
+
+    #[test]
+    fn test_discovery() {
+        let root = tempfile::tempdir().unwrap();
+        let dir = root.path().join("project").join("sub");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            root.path().join(FILE_NAME),
+            "[confirmations]\nconfirm = \"once\"\nallow_override = \"deny\"\n",
+        )
+        .unwrap();
+        fs::write(
+            root.path().join("project").join(FILE_NAME),
+            "[confirmations]\nconfirm = \"never\"\n[git]\nignore = true\n",
+        )
+        .unwrap();
+
+        let user = root.path().join("config.toml");
+        fs::write(
+            &user,
+            "[git]\nignore = false\n[paths]\ncanonicalize_paths = true\n",
+        )
+        .unwrap();
+        let config = Config::discover(Some(user), &dir).unwrap();
+        let options = configured(&config, &["rens-cli", "sed", "/a/b/", "."]);
+        assert_eq!(options.confirmations.confirm, ConfirmOption::Never);
+        assert_eq!(options.confirmations.allow_override, OverrideOption::Deny);
+        assert!(options.git_opt.auto_ignore);
+        assert!(options.paths_opt.canonicalize_paths);
+
+        fs::write(dir.join(FILE_NAME), "[git]\nfoo = true\n").unwrap();
+        assert!(Config::discover(None, &dir)
+            .unwrap_err()
+            .to_string()
+            .contains("unknown field `foo`"));
+        fs::write(dir.join(FILE_NAME), "[pattern]\ncase_insensitve = true\n")
+            .unwrap();
+        assert!(Config::discover(None, &dir)
+            .unwrap_err()
+            .to_string()
+            .contains("unknown field `case_insensitve`"));
+    }
+
+    #[test]
+    fn test_command_line_wins() {
+        let config = Config {
+            sections: toml::from_str(
+                "[confirmations]\nconfirm = \"never\"\n\
+                 [recursion]\nrecursive = true\ndepth = 2\n",
+            )
+            .unwrap(),
+        };
+
+        let options = configured(
+            &config,
+            &[
+                "rens-cli",
+                "sed",
+                "--confirm=each",
+                "--depth=5",
+                "/a/b/",
+                ".",
+            ],
+        );
+        assert_eq!(options.confirmations.confirm, ConfirmOption::Each);
+        assert_eq!(options.recursion.depth, Some(5));
+        assert!(options.recursion.recursive);
+    }
+}
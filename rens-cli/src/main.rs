@@ -1,35 +1,62 @@
 /* Modules */
 mod cli;
+mod config;
+mod recipe;
 mod utils;
 /* Built-in imports */
-use std::{fs, io};
+use std::{env, fs, io, path::PathBuf, time::SystemTime};
 /* Crate imports */
 use cli::{
-    renaming::options::{ConfirmOption, GitOpt, Options},
+    renaming::options::{
+        ConfirmOption, Confirmations, GitOpt, JournalOpt, Options,
+        OutputFormat, OutputOpt, PathsOpt, Recursion, Selection, SequenceOpt,
+    },
     Cli, Commands,
 };
-use utils::ask_for_confirm;
+use config::{user_config, Config};
+use recipe::Recipe;
+use utils::{ask_for_confirm, edit_text, plain_line, stdin_paths};
 /* Dependencies */
-use clap::{CommandFactory, Parser};
-use ignore::{overrides::OverrideBuilder, WalkBuilder};
+use clap::{CommandFactory as _, FromArgMatches as _};
 use log::{debug, error, info};
+#[cfg(feature = "media")]
+use rens_common::template::Tags;
 use rens_common::{
-    traits::{BoolExt, IteratorExt, ResultIteratorExt},
-    File,
+    journal, listing, mapping, os_bytes,
+    predicate::Predicates,
+    template::{Digests, Sequence},
+    traits::{BoolExt as _, IteratorExt as _, ResultIteratorExt as _},
+    walk::Walker,
+    File, Journal, Plan, Rename, RenameTarget, Strategy, COMPOUND_EXTENSIONS,
 };
-use tap::{Pipe, Tap};
+use tap::{Pipe as _, Tap as _};
 
 #[allow(clippy::too_many_lines)]
 fn main() -> anyhow::Result<()> {
+    let matches = Cli::command().get_matches();
     let Cli {
-        command,
+        command: parsed,
         verbose: _,
-    } = Cli::parse().tap(|options| {
-        env_logger::Builder::new()
-            .filter_level(options.verbose.log_level_filter())
-            .init();
-        debug!("{options:#?}");
-    });
+    } = Cli::from_arg_matches(&matches)
+        .unwrap_or_else(|err| err.exit())
+        .tap(|options| {
+            env_logger::Builder::new()
+                .filter_level(options.verbose.log_level_filter())
+                .init();
+            debug!("{options:#?}");
+        });
+    // Apply the configured defaults to the subcommand's options, only
+    // loading the configuration when it's used
+    let command = if parsed.is_configurable() {
+        Config::discover(user_config(), &env::current_dir()?)?
+            .configure(
+                parsed,
+                matches.subcommand().map_or(&matches, |(_, sub)| sub),
+            )?
+            .tap(|configured| debug!("Configured: {configured:#?}"))
+    } else {
+        parsed
+    };
 
     match command {
         Commands::Completions { shell } => {
@@ -51,119 +78,413 @@ fn main() -> anyhow::Result<()> {
             clap_mangen::Man::new(cmd).generate_to(path)?;
         },
         Commands::Renaming(mode) => {
-            let (
-                strategy,
-                Options {
-                    git_opt: GitOpt { auto_ignore },
-                    confirmations,
-                    paths_opt,
-                    recursion,
-                    target,
-                    paths,
-                },
-            ) = mode.get_strategy_and_options();
-
-            let files = paths
+            let recipe = Recipe::from(mode);
+            if let Some(path) = recipe.options().save_recipe.as_deref() {
+                recipe.save(path)?;
+                info!("Recipe saved to {}", os_bytes::escape_path(path));
+            }
+            let (strategy, options) = recipe.into_strategy_and_options()?;
+            rename(strategy, options)?;
+        },
+        Commands::Run {
+            recipe,
+            paths,
+            dry_run,
+            journal_opt,
+            paths_opt,
+        } => {
+            let (strategy, mut options) = Recipe::load(&recipe)
+                .and_then(Recipe::into_strategy_and_options)
+                .map_err(|err| {
+                    anyhow::anyhow!("{}: {err}", os_bytes::escape_path(&recipe))
+                })?;
+            options.paths = paths;
+            options.output_opt.dry_run = dry_run;
+            options.journal_opt = journal_opt;
+            // Stdin is read when running, canonicalizing may be recorded
+            options.paths_opt = PathsOpt {
+                canonicalize_paths: paths_opt.canonicalize_paths
+                    || options.paths_opt.canonicalize_paths,
+                ..paths_opt
+            };
+            rename(strategy, options)?;
+        },
+        Commands::Edit {
+            paths,
+            confirmations,
+            git_opt: GitOpt { auto_ignore, .. },
+            journal_opt: JournalOpt { journal },
+            output_opt,
+            paths_opt,
+            recursion,
+            selection,
+        } => {
+            let files = find_files(
+                paths,
+                auto_ignore,
+                &paths_opt,
+                &recursion,
+                &selection,
+            )?;
+            if files.is_empty() {
+                println!("Nothing to do...");
+                return Ok(());
+            }
+
+            let edited = edit_text(&listing::write(&files))?;
+            listing::parse(&files, &edited)?
                 .into_iter()
-                // remove dir paths if recursive mode is disabled
-                .filter(|path| recursion.recursive || !path.is_dir())
-                // if recursive mode is enabled turn all dir paths into their child files paths
-                .flat_map_if(
-                    |path| path.is_dir(),
-                    |path| {
-                        WalkBuilder::new(&path)
-                            .hidden(recursion.allow_hidden)
-                            .max_depth(recursion.depth)
-                            .require_git(false) // dunno
-                            .git_exclude(auto_ignore)
-                            .git_global(auto_ignore)
-                            .git_ignore(auto_ignore)
-                            .ignore(auto_ignore)
-                            // assume that parsing ignore files also
-                            // means that we want to ignore the `.git` dir and `.gitignore` files themselves
-                            .pipe(
-                                #[allow(clippy::expect_used)]
-                                |builder| {
-                                    if auto_ignore {
-                                        builder.overrides(
-                                            OverrideBuilder::new(path)
-                                                .add("!/.git/")
-                                                .expect("unreachable")
-                                                .add("!/.gitignore")
-                                                .expect("unreachable")
-                                                .build()
-                                                .expect("unreachable"),
-                                        )
-                                    } else {
-                                        builder
-                                    }
-                                },
-                            )
-                            .build()
-                            .filter_map_ok(|err| error!("{err}"))
-                            .map(|dir_entry| dir_entry.path().to_path_buf())
-                            .filter(|entry_path| !entry_path.is_dir())
-                    },
-                )
-                .map_if(
-                    |_| paths_opt.canonicalize_paths,
-                    // ensured in path parsing
-                    #[allow(clippy::expect_used)]
-                    |path| {
-                        dunce::canonicalize(path)
-                            .expect("Canonicalization failed")
-                    },
+                .pipe(|renames| {
+                    apply(
+                        renames,
+                        &confirmations,
+                        &output_opt,
+                        journal,
+                        &COMPOUND_EXTENSIONS.map(str::to_owned),
+                    )
+                })?;
+        },
+        Commands::Sanitize {
+            paths,
+            sanitize_opt,
+            confirmations,
+            git_opt: GitOpt { auto_ignore, .. },
+            journal_opt: JournalOpt { journal },
+            output_opt,
+            paths_opt,
+            recursion,
+            selection,
+        } => {
+            let policy = sanitize_opt.policy()?;
+            find_files(paths, auto_ignore, &paths_opt, &recursion, &selection)?
+                .into_iter()
+                .filter_map(|file| {
+                    let (name, rules) = policy.sanitize(file.name());
+                    if rules.is_empty() {
+                        info!(
+                            "Nothing to do for {}",
+                            os_bytes::escape_path(&file.path())
+                        );
+                        return None;
+                    }
+                    let broken = rules
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    let path = os_bytes::escape_path(&file.path());
+                    // Keep JSON and TSV outputs parsable
+                    if output_opt.output == OutputFormat::Plain {
+                        println!("{path}: {broken}");
+                    } else {
+                        info!("{path}: {broken}");
+                    }
+                    let to = file.parent().join(name.to_os_string());
+                    Some(Rename::new(file, to))
+                })
+                .pipe(|renames| {
+                    apply(
+                        renames,
+                        &confirmations,
+                        &output_opt,
+                        journal,
+                        &COMPOUND_EXTENSIONS.map(str::to_owned),
+                    )
+                })?;
+        },
+        Commands::Map {
+            file,
+            format,
+            confirmations,
+            journal_opt: JournalOpt { journal },
+            output_opt,
+        } => {
+            mapping::load(
+                &file,
+                format.unwrap_or_else(|| mapping::Format::from_path(&file)),
+            )?
+            .into_iter()
+            .map(|pair| {
+                File::from_path(pair.from())
+                    .map(|found| Rename::new(found, pair.to().clone()))
+                    .map_err(|err| {
+                        format!("{}: {err}", os_bytes::escape_path(pair.from()))
+                    })
+            })
+            .filter_map_ok(|err| error!("{err}"))
+            .pipe(|renames| {
+                apply(
+                    renames,
+                    &confirmations,
+                    &output_opt,
+                    journal,
+                    &COMPOUND_EXTENSIONS.map(str::to_owned),
                 )
-                .map(File::from_path)
+            })?;
+        },
+        Commands::Undo {
+            journal,
+            confirmations,
+            output_opt,
+        } => {
+            Journal::load(&journal, journal::Format::from_path(&journal))?
+                .reversed()
                 .filter_map_ok(|err| error!("{err}"))
-                // Filter those for which nothing needs to be done
-                .filter(|file| {
-                    file.needs_rename(&strategy, target).tap_if_false(|| {
-                        info!("Nothing to do for {}", file.path().display());
-                    })
-                })
-                // Log every rename that can be done
-                .tap_for_each(|file| {
-                    println!(
-                        "{} -> {}",
-                        file.path().display(),
-                        file.renamed_name(&strategy, target)
+                .pipe(|renames| {
+                    apply(
+                        renames,
+                        &confirmations,
+                        &output_opt,
+                        None,
+                        &COMPOUND_EXTENSIONS.map(str::to_owned),
+                    )
+                })?;
+        },
+    }
+
+    Ok(())
+}
+
+/// Renames the files found in `options.paths` following `unnumbered`.
+fn rename(unnumbered: Strategy, options: Options) -> anyhow::Result<()> {
+    let Options {
+        git_opt: GitOpt { auto_ignore, .. },
+        journal_opt: JournalOpt { journal },
+        output_opt,
+        confirmations,
+        paths_opt,
+        recursion,
+        selection,
+        sequence_opt: SequenceOpt { start, step, sort },
+        target,
+        compound_extensions,
+        paths,
+        expressions: _,
+        normalize: _,
+        save_recipe: _,
+    } = options;
+
+    let configured = unnumbered
+        .with_compound_extensions(compound_extensions)
+        .with_base(env::current_dir()?);
+    let mut files =
+        find_files(paths, auto_ignore, &paths_opt, &recursion, &selection)?;
+    // Renamed paths are relative to the current directory, refuse the
+    // files out of it before renaming anything
+    if target == RenameTarget::Path {
+        if let Some(outside) = files
+            .iter()
+            .find(|file| file.relative_path(configured.base()).is_none())
+        {
+            anyhow::bail!(
+                "{} isn't below the current directory, which `--target path` \
+                 renames paths relative to.",
+                os_bytes::escape_path(&outside.path())
+            );
+        }
+    }
+    // Only the matching files are numbered and hashed
+    files.retain(|file| {
+        file.matches(&configured, target).tap_if_false(|| {
+            info!("Nothing to do for {}", os_bytes::escape_path(&file.path()));
+        })
+    });
+
+    // Number files in the requested order
+    sort.sort(&mut files);
+    let sequence = Sequence::new(start, step, &files);
+    // Hash every file at once, in parallel
+    let digests = Digests::compute(&files, &configured.algorithms());
+    let hashed = configured.with_sequence(sequence).with_digests(digests);
+    // Read the media tags ahead as well
+    #[cfg(feature = "media")]
+    let strategy = {
+        let tags = Tags::read(&files, &hashed.tags());
+        hashed.with_tags(tags)
+    };
+    #[cfg(not(feature = "media"))]
+    let strategy = hashed;
+
+    files
+        .into_iter()
+        // Filter those for which nothing needs to be done
+        .filter_map(|file| {
+            let renamed_path = file
+                .renamed_path(&strategy, target)
+                .map_err(|err| error!("{err}"))
+                .ok()?;
+            (renamed_path != file.path())
+                .tap_if_false(|| {
+                    info!(
+                        "Nothing to do for {}",
+                        os_bytes::escape_path(&file.path())
                     );
                 })
-                // If needed, ask for confirmation
-                .filter(|_| {
-                    confirmations.confirm != ConfirmOption::Each
-                        || ask_for_confirm("Ok to rename?")
+                .then(|| {
+                    Rename::new(file, renamed_path)
+                        .creating_dirs(target == RenameTarget::Path)
                 })
-                .pipe(Iterator::collect::<Vec<_>>);
+        })
+        .pipe(|renames| {
+            apply(
+                renames,
+                &confirmations,
+                &output_opt,
+                journal,
+                strategy.compound_extensions(),
+            )
+        })
+}
 
-            if files.is_empty() {
-                println!("Nothing to do...");
-                return Ok(());
-            }
+/// Walks `paths` and gathers the selected files.
+fn find_files(
+    paths: Vec<PathBuf>,
+    auto_ignore: bool,
+    paths_opt: &PathsOpt,
+    recursion: &Recursion,
+    selection: &Selection,
+) -> anyhow::Result<Vec<File>> {
+    let walker = Walker::new(
+        recursion.recursive,
+        recursion.depth,
+        recursion.allow_hidden,
+        auto_ignore,
+    );
+    let filter = selection.filter(&env::current_dir()?)?;
+    let predicates = selection.predicates(SystemTime::now())?;
+    Ok(collect_files(
+        walker.walk_filtered(input_paths(paths, paths_opt), filter),
+        selection,
+        &predicates,
+        paths_opt,
+    ))
+}
 
-            // If needed, ask for global confirmation
-            if confirmations.confirm == ConfirmOption::Once
-                && !ask_for_confirm("All good ?")
-            {
-                println!("Canceled...");
-                return Ok(());
-            }
+/// The given `paths`, followed by those read from stdin if asked to.
+fn input_paths(
+    paths: Vec<PathBuf>,
+    paths_opt: &PathsOpt,
+) -> impl Iterator<Item = PathBuf> {
+    paths.into_iter().chain(
+        paths_opt
+            .from_stdin
+            .then(|| stdin_paths(paths_opt.null))
+            .into_iter()
+            .flatten(),
+    )
+}
 
-            files
-                .into_iter()
-                // Check overrides and ask if necessary
-                .filter(|file| {
-                    !file.renamed_path(&strategy, target).exists()
-                        || confirmations.allow_override.can_override(&format!(
-                            "{} -> {}",
-                            file.path().display(),
-                            file.renamed_name(&strategy, target)
-                        ))
-                })
-                .filter_map(|file| file.rename(&strategy, target).err())
-                .for_each(|err| error!("{err}"));
-        },
+/// Gathers the selected files among the walked `paths`.
+fn collect_files<I>(
+    paths: I,
+    selection: &Selection,
+    predicates: &Predicates,
+    paths_opt: &PathsOpt,
+) -> Vec<File>
+where
+    I: Iterator<Item = Result<PathBuf, ignore::Error>>,
+{
+    paths
+        .filter_map_ok(|err| error!("{err}"))
+        // remove dir paths unless directories get renamed
+        .filter(|path| selection.dirs() || !path.is_dir())
+        .map_if(
+            |_| paths_opt.canonicalize_paths,
+            // paths read from stdin may not exist, reported right after
+            |path| dunce::canonicalize(&path).unwrap_or(path),
+        )
+        .map(|path| {
+            File::from_path(&path).map_err(|err| {
+                format!("{}: {err}", os_bytes::escape_path(&path))
+            })
+        })
+        .filter_map_ok(|err| error!("{err}"))
+        .filter(|file| selection.matches(file.kind()))
+        .filter(|file| predicates.matches(file))
+        .collect()
+}
+
+/// Confirms, checks overrides and executes the renames,
+/// recording them in `journal` if given.
+fn apply<I>(
+    renames: I,
+    confirmations: &Confirmations,
+    output_opt: &OutputOpt,
+    journal: Option<PathBuf>,
+    compound: &[String],
+) -> anyhow::Result<()>
+where
+    I: Iterator<Item = Rename>,
+{
+    let planned = confirmations
+        .on_collision
+        .resolve(renames.collect(), compound);
+
+    if output_opt.dry_run {
+        output_opt.output.print(&planned)?;
+        return Ok(());
+    }
+
+    // Only remaining if asked to abort
+    if !planned.collisions().is_empty() {
+        anyhow::bail!("Some renames collide, aborting.");
+    }
+
+    let plan = if confirmations.confirm == ConfirmOption::Each {
+        let conflicts = planned
+            .with_conflicts()
+            .map(|(_, conflict)| conflict)
+            .collect::<Vec<_>>();
+        planned
+            .into_iter()
+            .zip(conflicts)
+            // Log every rename and ask for confirmation
+            .filter(|pending| {
+                println!("{}", plain_line(&pending.0, pending.1));
+                ask_for_confirm("Ok to rename?")
+            })
+            .map(|(rename, _)| rename)
+            .collect::<Plan>()
+    } else {
+        // Log every rename that can be done
+        output_opt.output.print(&planned)?;
+        planned
+    };
+
+    if plan.is_empty() {
+        println!("Nothing to do...");
+        return Ok(());
+    }
+
+    // If needed, ask for global confirmation
+    if confirmations.confirm == ConfirmOption::Once
+        && !ask_for_confirm("All good ?")
+    {
+        println!("Canceled...");
+        return Ok(());
+    }
+
+    // Check overrides and ask if necessary
+    let approved = plan.filter_overrides(|rename| {
+        confirmations
+            .allow_override
+            .can_override(&rename.to_string())
+    });
+    // Written along the renames, so that an interruption can be undone
+    let mut record = journal
+        .as_ref()
+        .map(|path| {
+            journal::Writer::create(path, journal::Format::from_path(path))
+        })
+        .transpose()?;
+    approved
+        .execute()
+        .filter_map_ok(|err| error!("{err}"))
+        .filter_map(|rename| record.as_mut()?.record(rename).err())
+        .for_each(|err| error!("{err}"));
+
+    if let Some(path) = journal {
+        info!("Journal written to {}", os_bytes::escape_path(&path));
     }
 
     Ok(())
@@ -0,0 +1,373 @@
+//! Renaming pipelines saved as TOML, run with `rens run`.
+//!
+//! A recipe lists `[[step]]` tables, applied in order like `--expression`s,
+//! followed by the renaming options grouped as in `--help`:
+//!
+//! ```toml
+//! target = "stem"
+//!
+//! [[step]]
+//! mode = "regex"
+//! pattern = "IMG_(\\d+)"
+//! with = "photo-{n:03}"
+//!
+//! [[step]]
+//! mode = "sed"
+//! pattern = "/-/_/"
+//!
+//! [[step]]
+//! mode = "normalize"
+//! form = "nfc"
+//!
+//! [recursion]
+//! recursive = true
+//! ```
+
+/* Built-in imports */
+use core::str::FromStr;
+use std::{fs, io, path::Path};
+/* Crate imports */
+use crate::cli::renaming::{
+    escaped,
+    options::{Form, Options, PatternOpt},
+    Mode,
+};
+/* Dependencies */
+use regex::bytes::Regex;
+use rens_common::{template::Transform, SedPattern, Strategy, Template};
+use serde::{de::IgnoredAny, Deserialize, Serialize};
+use toml::{de, ser};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Recipe {
+    #[serde(rename = "step")]
+    steps: Vec<Step>,
+    #[serde(flatten)]
+    options: Options,
+}
+
+/// Top-level keys a recipe can hold, to reject the misspelled ones
+/// the flattened options would ignore.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[allow(dead_code)]
+struct Keys {
+    step: Option<IgnoredAny>,
+    target: Option<IgnoredAny>,
+    compound_extensions: Option<IgnoredAny>,
+    confirmations: Option<IgnoredAny>,
+    git: Option<IgnoredAny>,
+    output: Option<IgnoredAny>,
+    paths: Option<IgnoredAny>,
+    recursion: Option<IgnoredAny>,
+    selection: Option<IgnoredAny>,
+    sequence: Option<IgnoredAny>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "lowercase")]
+pub enum Step {
+    String {
+        pattern: String,
+        with: String,
+        #[serde(flatten)]
+        options: PatternOpt,
+    },
+    Regex {
+        pattern: String,
+        with: String,
+        #[serde(flatten)]
+        options: PatternOpt,
+    },
+    Sed {
+        pattern: String,
+    },
+    Normalize {
+        form: Form,
+    },
+}
+
+impl Step {
+    /// Compiles the step, like the matching renaming mode does.
+    fn export(self) -> Result<(Regex, Template, usize), String> {
+        let parsed_with = |with: &str| {
+            with.parse::<Template>().map_err(|err| err.to_string())
+        };
+        match self {
+            Self::String {
+                pattern,
+                with,
+                options,
+            } => {
+                let (regex, limit) = options.apply(escaped(&pattern));
+                Ok((regex, parsed_with(&with)?, limit))
+            },
+            Self::Regex {
+                pattern,
+                with,
+                options,
+            } => {
+                let regex =
+                    Regex::new(&pattern).map_err(|err| err.to_string())?;
+                let (applied, limit) = options.apply(regex);
+                Ok((applied, parsed_with(&with)?, limit))
+            },
+            Self::Sed { pattern } => pattern
+                .parse::<SedPattern>()
+                .map(SedPattern::export)
+                .map_err(|err| err.to_string()),
+            Self::Normalize { form } => Ok(Transform::from(form).export()),
+        }
+    }
+}
+
+impl From<Mode> for Recipe {
+    /// Recipe running the same renaming, `--expression`s included.
+    fn from(mode: Mode) -> Self {
+        let (first, options) = match mode {
+            Mode::String {
+                pattern,
+                with,
+                pattern_opt,
+                options,
+            } => (
+                Step::String {
+                    pattern,
+                    with: with.to_string(),
+                    options: pattern_opt,
+                },
+                options,
+            ),
+            Mode::Regex {
+                pattern,
+                with,
+                pattern_opt,
+                options,
+            } => (
+                Step::Regex {
+                    pattern: pattern.as_str().to_owned(),
+                    with: with.to_string(),
+                    options: pattern_opt,
+                },
+                options,
+            ),
+            Mode::Sed {
+                sed_pattern,
+                options,
+            } => (
+                Step::Sed {
+                    pattern: sed_pattern.to_string(),
+                },
+                options,
+            ),
+            Mode::Normalize { form, options } => {
+                (Step::Normalize { form }, options)
+            },
+        };
+        let steps = options
+            .expressions
+            .iter()
+            .map(|expression| Step::Sed {
+                pattern: expression.to_string(),
+            })
+            .chain(options.normalize.map(|form| Step::Normalize { form }))
+            .fold(vec![first], |mut steps, step| {
+                steps.push(step);
+                steps
+            });
+        Self { steps, options }
+    }
+}
+
+impl Recipe {
+    #[inline]
+    pub const fn options(&self) -> &Options {
+        &self.options
+    }
+
+    #[inline]
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        fs::read_to_string(path)?.parse()
+    }
+
+    #[inline]
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        fs::write(path, toml::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Builds the strategy chaining every step, and the options to run it.
+    ///
+    /// Note: The renaming modes go through their recipe too,
+    /// so a saved recipe behaves exactly like the command line.
+    #[inline]
+    pub fn into_strategy_and_options(
+        self,
+    ) -> Result<(Strategy, Options), Error> {
+        let mut steps =
+            self.steps.into_iter().zip(1_usize..).map(|(step, nb)| {
+                step.export().map_err(|err| Error::InvalidStep(nb, err))
+            });
+        let (pattern, with, limit) = steps.next().ok_or(Error::NoStep)??;
+        let strategy = steps.try_fold(
+            Strategy::new(pattern, with, limit),
+            |chained, exported| {
+                let (next_pattern, next_with, next_limit) = exported?;
+                Ok::<_, Error>(chained.then(
+                    next_pattern,
+                    next_with,
+                    next_limit,
+                ))
+            },
+        )?;
+        Ok((strategy, self.options))
+    }
+}
+
+impl FromStr for Recipe {
+    type Err = Error;
+
+    #[inline]
+    fn from_str(content: &str) -> Result<Self, Self::Err> {
+        toml::from_str::<Keys>(content)?;
+        Ok(toml::from_str(content)?)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("The recipe doesn't have any step.")]
+    NoStep,
+    #[error("Step {0} is invalid: {1}")]
+    InvalidStep(usize, String),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Parse(#[from] de::Error),
+    #[error(transparent)]
+    Serialize(#[from] ser::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+    use rens_common::{File, RenameTarget};
+
+    #[derive(Debug, Parser)]
+    struct TestParser {
+        #[command(subcommand)]
+        pub mode: Mode,
+    }
+
+    fn recipe(args: &[&str]) -> Recipe {
+        let mut all = vec!["rens-cli"];
+        all.extend(args);
+        Recipe::from(TestParser::parse_from(all).mode)
+    }
+
+    #[test]
+    fn test_defaults() {
+        let parsed: Recipe =
+            toml::from_str("[[step]]\nmode = \"sed\"\npattern = \"/a/b/\"\n")
+                .unwrap();
+        // Omitted options get the same values as omitted flags
+        assert_eq!(
+            toml::to_string(&parsed).unwrap(),
+            toml::to_string(&recipe(&["sed", "/a/b/", "."])).unwrap()
+        );
+        toml::from_str::<Recipe>("[recursion]\nfoo = 1\n").unwrap_err();
+    }
+
+    #[test]
+    fn test_unknown_keys() {
+        let step = "[[step]]\nmode = \"sed\"\npattern = \"/a/b/\"\n";
+        step.parse::<Recipe>().unwrap();
+        for misspelled in ["recursve = true\n", "[confirmation]\n"] {
+            let err =
+                format!("{misspelled}{step}").parse::<Recipe>().unwrap_err();
+            assert!(err.to_string().contains("unknown field"), "{err}");
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("recipe.toml");
+        let saved = recipe(&[
+            "regex",
+            "-o2",
+            "--case-insensitive",
+            "--target=stem",
+            "-r",
+            "--confirm=never",
+            "--journal=rens.json",
+            "-e/b/c/",
+            "(a+)",
+            "{$1:upper}",
+            ".",
+        ]);
+        saved.save(&path).unwrap();
+        // Each run gets its own journal
+        assert!(!fs::read_to_string(&path).unwrap().contains("journal"));
+
+        let loaded = Recipe::load(&path).unwrap();
+        assert_eq!(loaded.steps, saved.steps);
+        assert_eq!(
+            loaded.steps.get(1),
+            Some(&Step::Sed {
+                pattern: "/b/c/".to_owned()
+            })
+        );
+        let (strategy, options) = loaded.into_strategy_and_options().unwrap();
+        assert_eq!(options.target, RenameTarget::Stem);
+        assert!(options.recursion.recursive);
+        fs::write(dir.path().join("aAbaa.txt"), "").unwrap();
+        let file = File::from_path(dir.path().join("aAbaa.txt")).unwrap();
+        assert_eq!(
+            file.renamed_name(&strategy, options.target).to_string(),
+            "AAcAA.txt"
+        );
+    }
+
+    #[test]
+    fn test_normalize() {
+        let post_step = recipe(&["sed", "--normalize=ascii", "/a/b/", "."]);
+        assert_eq!(
+            post_step.steps.last(),
+            Some(&Step::Normalize { form: Form::Ascii })
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let decomposed = dir.path().join("cafe\u{301}.txt");
+        fs::write(&decomposed, "").unwrap();
+        let (strategy, options) = recipe(&["normalize", "nfc", "."])
+            .into_strategy_and_options()
+            .unwrap();
+        assert_eq!(
+            File::from_path(decomposed)
+                .unwrap()
+                .renamed_name(&strategy, options.target)
+                .to_string(),
+            "caf\u{e9}.txt"
+        );
+    }
+
+    #[test]
+    fn test_invalid_steps() {
+        let invalid = |steps: &str| {
+            toml::from_str::<Recipe>(steps)
+                .unwrap()
+                .into_strategy_and_options()
+                .unwrap_err()
+                .to_string()
+        };
+        assert_eq!(invalid("step = []"), "The recipe doesn't have any step.");
+        assert!(invalid(
+            "[[step]]\nmode = \"string\"\npattern = \"a\"\nwith = \"b\"\n\
+             [[step]]\nmode = \"regex\"\npattern = \"(\"\nwith = \"b\"\n"
+        )
+        .starts_with("Step 2 is invalid"));
+    }
+}
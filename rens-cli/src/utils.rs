@@ -1,17 +1,30 @@
 /* Built-in imports */
-use std::io::{self, Write};
+use std::{
+    env, fs,
+    io::{self, BufRead as _, BufReader, IsTerminal as _, Write as _},
+    path::{Path, PathBuf},
+    process::Command,
+};
 /* Crate imports */
-use crate::cli::renaming::options::OverrideOption;
+use crate::cli::renaming::options::{
+    CollisionOption, OutputFormat, OverrideOption,
+};
+/* Dependencies */
+use log::{error, log, Level};
+use rens_common::{os_bytes, plan::Conflict, tsv, Plan, Rename};
+use serde::Serialize;
+use tap::Pipe as _;
 
 #[allow(clippy::expect_used)]
 pub fn ask_for_confirm(prompt: &str) -> bool {
     loop {
         print!("{prompt} (yes/no): ");
-        let mut input = String::new();
         io::stdout().lock().flush().expect("Failed to flush stdin.");
-        io::stdin()
-            .read_line(&mut input)
-            .expect("Failed to read stdin.");
+        let Some(input) = read_answer().expect("Failed to read stdin.") else {
+            // Nobody to answer
+            println!();
+            return false;
+        };
 
         match input.trim().to_lowercase().as_str() {
             "yes" | "y" => return true,
@@ -21,6 +34,76 @@ pub fn ask_for_confirm(prompt: &str) -> bool {
     }
 }
 
+/// Reads a line from stdin, or from the terminal once stdin is exhausted
+/// (e.g. by `--from-stdin`), `None` if neither can answer.
+fn read_answer() -> io::Result<Option<String>> {
+    let mut input = String::new();
+    if io::stdin().read_line(&mut input)? > 0 {
+        return Ok(Some(input));
+    }
+    if let Some(tty) = terminal() {
+        if BufReader::new(tty).read_line(&mut input)? > 0 {
+            return Ok(Some(input));
+        }
+    }
+    Ok(None)
+}
+
+/// The controlling terminal, if any.
+fn terminal() -> Option<fs::File> {
+    #[cfg(unix)]
+    return fs::File::open("/dev/tty").ok();
+    #[cfg(windows)]
+    return fs::File::open("CONIN$").ok();
+    #[cfg(not(any(unix, windows)))]
+    None
+}
+
+/// Paths read from stdin, separated by newlines or NUL characters.
+pub fn stdin_paths(null: bool) -> impl Iterator<Item = PathBuf> {
+    io::stdin()
+        .lock()
+        .split(if null { b'\0' } else { b'\n' })
+        .map_while(|read| {
+            read.map_err(|err| error!("Failed to read stdin: {err}"))
+                .ok()
+        })
+        .filter(|bytes| !bytes.is_empty())
+        .map(|bytes| PathBuf::from(os_bytes::into_os_string(bytes)))
+}
+
+/// Lets the user edit `text` using `$VISUAL`, `$EDITOR` or `vi`.
+pub fn edit_text(text: &str) -> io::Result<String> {
+    let mut file = tempfile::Builder::new()
+        .prefix("rens-")
+        .suffix(".txt")
+        .tempfile()?;
+    file.write_all(text.as_bytes())?;
+    file.flush()?;
+
+    let editor = ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| env::var(var).ok())
+        .find(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_owned());
+    // Editors may come with arguments, like `code --wait`
+    let mut words = editor.split_whitespace();
+    let mut command = Command::new(words.next().unwrap_or("vi"));
+    command.args(words).arg(file.path());
+    // Don't hand a piped stdin to the editor
+    if !io::stdin().is_terminal() {
+        if let Some(tty) = terminal() {
+            command.stdin(tty);
+        }
+    }
+    let status = command.status()?;
+    if !status.success() {
+        return Err(io::Error::other(format!("`{editor}` failed ({status}).")));
+    }
+
+    fs::read_to_string(file.path())
+}
+
 impl OverrideOption {
     pub fn can_override(&self, rename_prompt: &str) -> bool {
         match *self {
@@ -33,3 +116,87 @@ impl OverrideOption {
         }
     }
 }
+
+impl CollisionOption {
+    /// Reports every collision and resolves them, suffixing before the
+    /// `compound` extensions, aborting leaves the plan as is.
+    pub fn resolve(&self, plan: Plan, compound: &[String]) -> Plan {
+        let level = if *self == Self::Abort {
+            Level::Error
+        } else {
+            Level::Warn
+        };
+        for (to, renames) in plan.collisions() {
+            log!(
+                level,
+                "{} files would be renamed to {}: {}",
+                renames.len(),
+                os_bytes::escape_path(to),
+                renames
+                    .iter()
+                    .map(|rename| os_bytes::escape_path(&rename.from()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+
+        match *self {
+            Self::Abort => plan,
+            Self::Skip => plan.skip_collisions(),
+            Self::Suffix => plan.suffix_collisions(compound),
+        }
+    }
+}
+
+/// Human readable line describing a rename.
+pub fn plain_line(rename: &Rename, conflict: Option<Conflict>) -> String {
+    conflict.map_or_else(
+        || rename.to_string(),
+        |kind| format!("{rename} (conflict: {kind})"),
+    )
+}
+
+#[derive(Serialize)]
+struct Entry<'plan> {
+    #[serde(serialize_with = "os_bytes::serde_path::serialize")]
+    from: PathBuf,
+    #[serde(serialize_with = "os_bytes::serde_path::serialize")]
+    to: &'plan Path,
+    conflict: Option<Conflict>,
+}
+
+impl OutputFormat {
+    pub fn print(self, plan: &Plan) -> serde_json::Result<()> {
+        match self {
+            Self::Plain => {
+                plan.with_conflicts().for_each(|(rename, conflict)| {
+                    println!("{}", plain_line(rename, conflict));
+                });
+            },
+            Self::Json => plan
+                .with_conflicts()
+                .map(|(rename, conflict)| Entry {
+                    from: rename.from(),
+                    to: rename.to(),
+                    conflict,
+                })
+                .collect::<Vec<_>>()
+                .pipe(|entries| serde_json::to_string_pretty(&entries))
+                .map(|json| println!("{json}"))?,
+            Self::Tsv => {
+                println!("from\tto\tconflict");
+                plan.with_conflicts().for_each(|(rename, conflict)| {
+                    println!(
+                        "{}\t{}\t{}",
+                        tsv::escape_path(&rename.from()),
+                        tsv::escape_path(rename.to()),
+                        conflict
+                            .map(|kind| kind.to_string())
+                            .unwrap_or_default()
+                    );
+                });
+            },
+        }
+        Ok(())
+    }
+}
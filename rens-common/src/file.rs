@@ -1,78 +1,136 @@
 /* Built-in imports */
-use core::fmt;
+use core::{fmt, ops::Range};
 use std::{
-    ffi::OsStr,
+    ffi::OsString,
     fs, io,
-    path::{Path, PathBuf},
+    path::{is_separator, Component, Path, PathBuf},
 };
 /* Dependencies */
+use bstr::ByteSlice as _;
 use derive_more::{Constructor, Display, FromStr};
+use serde::{Deserialize, Serialize};
 
-use crate::{
-    traits::{FileKind, PathExt},
-    Strategy,
-};
+use crate::{os_bytes, traits::FileKind, Metadata, Strategy};
 
-#[derive(Debug, Constructor, PartialEq, Eq, PartialOrd, Ord)]
+/// Name of a file, as bytes since it may not be valid UTF-8.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Name {
-    stem: String,
-    extension: Option<String>,
+    /// Split at the last dot, to sort names stem first.
+    stem: Vec<u8>,
+    extension: Option<Vec<u8>>,
 }
 
 impl Name {
+    /// The name without its extension, see [`Name::range`].
     #[inline]
     #[must_use]
-    pub const fn stem(&self) -> &String {
-        &self.stem
+    pub fn stem(&self, compound: &[String]) -> Vec<u8> {
+        self.part(RenameTarget::Stem, compound).unwrap_or_default()
     }
 
+    /// The last extension, or a `compound` one, see [`Name::range`].
     #[inline]
     #[must_use]
-    pub const fn extension(&self) -> &Option<String> {
-        &self.extension
+    pub fn extension(&self, compound: &[String]) -> Option<Vec<u8>> {
+        self.part(RenameTarget::Extension, compound)
+    }
+
+    fn part(
+        &self,
+        target: RenameTarget,
+        compound: &[String],
+    ) -> Option<Vec<u8>> {
+        let range = self.range(target, compound)?;
+        self.to_bytes().get(range).map(<[u8]>::to_vec)
     }
 
+    /// Byte range of the `target` part of the name, `None` if there isn't
+    /// any (e.g. the extension of `README`) or if it spans more than the name
+    /// ([`RenameTarget::Path`]).
+    ///
+    /// Note: `compound` extensions like `tar.gz` are kept whole.
     #[inline]
     #[must_use]
-    pub fn to_renamed(
+    pub fn range(
         &self,
-        strategy: &Strategy,
         target: RenameTarget,
-    ) -> Self {
+        compound: &[String],
+    ) -> Option<Range<usize>> {
+        let bytes = self.to_bytes();
+        let len = bytes.len();
         match target {
-            RenameTarget::Both => {
-                let file_name = self.to_string();
-                let renamed = strategy.pattern.replacen(
-                    &file_name,
-                    strategy.limit,
-                    &strategy.with,
-                );
-                match renamed.rsplit_once('.') {
-                    Some((stem, ext)) => Self {
-                        stem: stem.to_owned(),
-                        extension: Some(ext.to_owned()),
-                    },
-                    None => Self {
-                        stem: renamed.to_string(),
-                        extension: None,
-                    },
-                }
+            RenameTarget::Both => Some(0..len),
+            RenameTarget::Path => None,
+            RenameTarget::Stem => Some(
+                0..extension_start(&bytes, compound)
+                    .map_or(len, |start| start.saturating_sub(1)),
+            ),
+            RenameTarget::Extension => {
+                extension_start(&bytes, compound).map(|start| start..len)
             },
-            RenameTarget::Stem => Self {
-                stem: strategy
-                    .pattern
-                    .replacen(self.stem(), strategy.limit, &strategy.with)
-                    .to_string(),
-                extension: self.extension().clone(),
+            RenameTarget::Extensions => {
+                let start = extension_start(&bytes, compound)?;
+                // Along with a compound extension right before, `tar.gz.gpg`
+                let chained = start
+                    .checked_sub(1)
+                    .and_then(|dot| bytes.get(..dot))
+                    .and_then(|head| compound_start(head, compound));
+                Some(chained.unwrap_or(start)..len)
             },
-            RenameTarget::Extension => Self {
-                stem: self.stem().clone(),
-                extension: self.extension().clone().map(|ext| {
-                    strategy
-                        .pattern
-                        .replacen(&ext, strategy.limit, &strategy.with)
-                        .to_string()
-                }),
+        }
+    }
+
+    /// Name with its `target` part replaced, see [`Name::range`].
+    #[inline]
+    #[must_use]
+    pub fn to_renamed<F>(
+        &self,
+        target: RenameTarget,
+        compound: &[String],
+        replace: F,
+    ) -> Self
+    where
+        F: Fn(&[u8]) -> Vec<u8>,
+    {
+        let Some(range) = self.range(target, compound) else {
+            return self.clone();
+        };
+        let bytes = self.to_bytes();
+        let mut renamed = bytes.get(..range.start).unwrap_or_default().to_vec();
+        renamed.extend(replace(bytes.get(range.clone()).unwrap_or_default()));
+        renamed.extend_from_slice(bytes.get(range.end..).unwrap_or_default());
+        Self::from_bytes(renamed)
+    }
+
+    /// The whole name, `stem.extension`.
+    #[inline]
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.stem.clone();
+        if let Some(ext) = self.extension.as_deref() {
+            bytes.push(b'.');
+            bytes.extend_from_slice(ext);
+        }
+        bytes
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn to_os_string(&self) -> OsString {
+        os_bytes::into_os_string(self.to_bytes())
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn from_bytes(bytes: Vec<u8>) -> Self {
+        match bytes.rsplit_once_str(".") {
+            Some((stem, ext)) if !stem.is_empty() => Self {
+                stem: stem.to_vec(),
+                extension: Some(ext.to_vec()),
+            },
+            _ => Self {
+                stem: bytes,
+                extension: None,
             },
         }
     }
@@ -80,25 +138,44 @@ impl Name {
     #[inline]
     pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
         let file_path = path.as_ref();
-        let stem = file_path
-            .file_stem()
-            .and_then(OsStr::to_str)
-            .map(ToString::to_string)
-            .ok_or(Error::NoFileStem(file_path.to_path_buf()))?;
-        let extension = file_path
-            .extension()
-            .and_then(OsStr::to_str)
-            .map(ToString::to_string);
-
-        Ok(Self::new(stem, extension))
+        file_path
+            .file_name()
+            .map(|name| Self::from_bytes(os_bytes::from_os_str(name)))
+            .ok_or_else(|| Error::NoFileStem(file_path.to_path_buf()))
     }
 }
 
-#[derive(Debug, Constructor, PartialEq, Eq)]
+/// Start of the extension in `bytes`, the longest of the `compound` ones
+/// matching, or what follows the last dot.
+fn extension_start(bytes: &[u8], compound: &[String]) -> Option<usize> {
+    compound_start(bytes, compound).or_else(|| {
+        bytes
+            .rfind_byte(b'.')
+            .filter(|&dot| dot > 0)
+            .map(|dot| dot + 1)
+    })
+}
+
+/// Start of the longest of the `compound` extensions `bytes` ends with.
+fn compound_start(bytes: &[u8], compound: &[String]) -> Option<usize> {
+    compound
+        .iter()
+        .filter_map(|ext| {
+            let start = bytes.len().checked_sub(ext.len())?;
+            let stem = bytes.get(..start)?.strip_suffix(b".")?;
+            (!stem.is_empty()
+                && bytes.get(start..)?.eq_ignore_ascii_case(ext.as_bytes()))
+            .then_some(start)
+        })
+        .min()
+}
+
+#[derive(Debug, Clone, Constructor, PartialEq, Eq)]
 pub struct File {
     name: Name,
     kind: FileKind,
     parent: PathBuf,
+    metadata: Metadata,
 }
 
 impl File {
@@ -120,10 +197,16 @@ impl File {
         &self.parent
     }
 
+    #[inline]
+    #[must_use]
+    pub const fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
     #[inline]
     #[must_use]
     pub fn path(&self) -> PathBuf {
-        self.parent.join(self.name().to_string())
+        self.parent.join(self.name().to_os_string())
     }
 
     #[inline]
@@ -132,12 +215,34 @@ impl File {
         let file_path = path.as_ref();
         let parent = file_path
             .parent()
-            .ok_or(Error::NoParent(file_path.to_path_buf()))?
+            .ok_or_else(|| Error::NoParent(file_path.to_path_buf()))?
             .to_path_buf();
-        let kind = file_path.kind()?;
+        let stat = fs::symlink_metadata(file_path)?;
         let name = Name::from_path(file_path)?;
 
-        Ok(Self { name, kind, parent })
+        Ok(Self {
+            name,
+            kind: FileKind::from(&stat),
+            parent,
+            metadata: Metadata::from(&stat),
+        })
+    }
+
+    /// Path of the file relative to `base`, `None` if it isn't below it.
+    #[inline]
+    #[must_use]
+    pub fn relative_path(&self, base: &Path) -> Option<PathBuf> {
+        let path = self.path();
+        let full = if path.is_relative() && base.is_absolute() {
+            base.join(&path)
+        } else {
+            path
+        };
+        let relative = without_cur_dir(&full)
+            .strip_prefix(without_cur_dir(base))
+            .ok()?
+            .to_path_buf();
+        is_plain(&relative).then_some(relative)
     }
 
     #[inline]
@@ -147,76 +252,204 @@ impl File {
         strategy: &Strategy,
         target: RenameTarget,
     ) -> Name {
-        self.name().to_renamed(strategy, target)
+        if target == RenameTarget::Path {
+            return self
+                .renamed_path(strategy, target)
+                .ok()
+                .and_then(|path| Name::from_path(path).ok())
+                .unwrap_or_else(|| self.name().clone());
+        }
+        self.name()
+            .to_renamed(target, strategy.compound_extensions(), |text| {
+                strategy.replace(text, self)
+            })
     }
 
+    /// New path of the file, in another directory when renaming its path.
+    ///
+    /// Renaming a part of the name can't introduce path separators,
+    /// it'd move the file.
+    ///
+    /// Note: Renaming the path of a file outside of the strategy's base
+    /// directory, or out of it (e.g. `../a`), fails.
     #[inline]
-    #[must_use]
     pub fn renamed_path(
         &self,
         strategy: &Strategy,
         target: RenameTarget,
-    ) -> PathBuf {
-        self.parent()
-            .join(self.name().to_renamed(strategy, target).to_string())
+    ) -> Result<PathBuf, Error> {
+        if target != RenameTarget::Path {
+            let name = self.renamed_name(strategy, target);
+            if name
+                .to_bytes()
+                .iter()
+                .any(|&byte| is_separator(byte.into()))
+            {
+                return Err(Error::Separator(self.path(), name.to_string()));
+            }
+            return Ok(self.parent().join(name.to_os_string()));
+        }
+        let path = self.path();
+        let relative =
+            self.relative_path(strategy.base()).ok_or_else(|| {
+                Error::OutsideBase(path.clone(), strategy.base().to_path_buf())
+            })?;
+        let renamed = PathBuf::from(os_bytes::into_os_string(
+            strategy
+                .replace(&os_bytes::from_os_str(relative.as_os_str()), self),
+        ));
+        if !is_plain(&renamed) {
+            return Err(Error::LeavesBase(path, renamed));
+        }
+        // Keeps the form of the path, relative or not
+        Ok(path
+            .ancestors()
+            .nth(relative.components().count())
+            .map_or_else(|| renamed.clone(), |prefix| prefix.join(&renamed)))
     }
 
+    /// Whether `strategy` matches the `target` part of the name, the file
+    /// being left untouched otherwise.
     #[inline]
     #[must_use]
+    pub fn matches(&self, strategy: &Strategy, target: RenameTarget) -> bool {
+        if target == RenameTarget::Path {
+            return self.relative_path(strategy.base()).is_some_and(|path| {
+                strategy.is_match(&os_bytes::from_os_str(path.as_os_str()))
+            });
+        }
+        let bytes = self.name().to_bytes();
+        self.name()
+            .range(target, strategy.compound_extensions())
+            .and_then(|range| bytes.get(range))
+            .is_some_and(|part| strategy.is_match(part))
+    }
+
+    #[inline]
     pub fn needs_rename(
         &self,
         strategy: &Strategy,
         target: RenameTarget,
-    ) -> bool {
-        self.name() != &self.name().to_renamed(strategy, target)
+    ) -> Result<bool, Error> {
+        Ok(self.path() != self.renamed_path(strategy, target)?)
     }
 
+    /// Renames the file, creating the missing directories when renaming
+    /// its path.
     #[inline]
     pub fn rename(
         &self,
         strategy: &Strategy,
         target: RenameTarget,
-    ) -> io::Result<()> {
-        fs::rename(self.path(), self.renamed_path(strategy, target))
+    ) -> Result<(), Error> {
+        let (from, to) = (self.path(), self.renamed_path(strategy, target)?);
+        prepare_move(&from, &to, target == RenameTarget::Path)?;
+        Ok(fs::rename(from, to)?)
+    }
+}
+
+/// Whether `path` is a non-empty relative path made of names only,
+/// so it can't escape the directory it's joined to.
+fn is_plain(path: &Path) -> bool {
+    path.components().next().is_some()
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+}
+
+fn without_cur_dir(path: &Path) -> PathBuf {
+    path.components()
+        .filter(|component| component != &Component::CurDir)
+        .collect()
+}
+
+/// Checks that `from` can be moved to `to`, creating the missing
+/// directories leading to `to` if asked to.
+pub(crate) fn prepare_move(
+    from: &Path,
+    to: &Path,
+    create_dirs: bool,
+) -> io::Result<()> {
+    if to != from && to.starts_with(from) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "{} can't be moved inside itself",
+                os_bytes::escape_path(from)
+            ),
+        ));
+    }
+    match to.parent() {
+        Some(dir)
+            if create_dirs && !dir.as_os_str().is_empty() && !dir.is_dir() =>
+        {
+            fs::create_dir_all(dir)
+        },
+        _ => Ok(()),
     }
 }
 
 impl fmt::Display for Name {
+    /// Escapes bytes that aren't valid UTF-8, see [`os_bytes::escape`].
     #[inline]
-    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            fmt,
-            "{}{}",
-            self.stem,
-            self.extension
-                .as_ref()
-                .map_or_else(String::new, |ext| format!(".{ext}"))
-        )
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", os_bytes::escape(&self.to_bytes()))
     }
 }
 
 #[derive(Debug, thiserror::Error)]
 #[non_exhaustive]
 pub enum Error {
-    #[error("{0} doesn't have a filestem.")]
+    #[error("{} doesn't have a filestem.", os_bytes::escape_path(.0))]
     NoFileStem(PathBuf),
-    #[error("{0} doesn't have a parent folder.")]
+    #[error("{} doesn't have a parent folder.", os_bytes::escape_path(.0))]
     NoParent(PathBuf),
+    #[error("{}: the new name {1} contains a path separator.", os_bytes::escape_path(.0))]
+    Separator(PathBuf, String),
+    #[error("{} isn't below {}, which its path is renamed relative to.", os_bytes::escape_path(.0), os_bytes::escape_path(.1))]
+    OutsideBase(PathBuf, PathBuf),
+    #[error("{}: the new path {} leaves the base directory.", os_bytes::escape_path(.0), os_bytes::escape_path(.1))]
+    LeavesBase(PathBuf, PathBuf),
     #[error("io::Error: {0}")]
     Io(#[from] io::Error),
 }
 
-#[derive(Debug, Default, Display, Clone, Copy, FromStr, PartialEq, Eq)]
+#[derive(
+    Debug,
+    Default,
+    Display,
+    Clone,
+    Copy,
+    FromStr,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
 #[non_exhaustive]
 pub enum RenameTarget {
-    /// File stem is the filename without the extension
-    /// See: <https://doc.rust-lang.org/std/path/struct.Path.html#method.file_stem>
+    /// The filename without its extension, `archive` of `archive.tar.gz`.
     Stem,
+    /// The last extension, or a compound one like `tar.gz`.
     Extension,
+    /// The last extension along with a compound one right before it,
+    /// `tar.gz.gpg` of `backup.tar.gz.gpg`.
+    ///
+    /// Note: other dots belong to the stem, `txt` of `v1.2.final.txt`.
+    Extensions,
     #[default]
     Both,
+    /// Path relative to the strategy's base directory, e.g.
+    /// `2023/01/photo.jpg`, to move files between directories.
+    Path,
 }
 
+/// Extensions made of several parts, renamed as a whole.
+pub const COMPOUND_EXTENSIONS: [&str; 7] = [
+    "tar.gz", "tar.bz2", "tar.xz", "tar.zst", "tar.lz4", "d.ts", "d.mts",
+];
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -241,6 +474,177 @@ mod tests {
                 name.to_string(),
                 Path::new(path).file_name().unwrap().to_string_lossy()
             );
+            assert_eq!(Name::from_bytes(name.to_bytes()), name);
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_non_utf8_name() {
+        use regex::bytes::Regex;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir
+            .path()
+            .join(os_bytes::into_os_string(b"caf\xe9 menu.TXT".to_vec()));
+        fs::write(&path, "").unwrap();
+        let file = File::from_path(&path).unwrap();
+        assert_eq!(file.name().to_string(), "caf\\xE9 menu.TXT");
+        assert_eq!(file.path(), path);
+
+        // Invalid bytes are kept as is, or matched with `(?-u:...)`
+        let strategy =
+            Strategy::new(Regex::new(" +").unwrap(), "_".parse().unwrap(), 0)
+                .then(
+                    Regex::new("[A-Z]+$").unwrap(),
+                    r"\L$0".parse().unwrap(),
+                    0,
+                );
+        assert_eq!(
+            file.renamed_name(&strategy, RenameTarget::Both).to_string(),
+            "caf\\xE9_menu.txt"
+        );
+        let latin1 = Strategy::new(
+            Regex::new(r"(?-u:[\xE0-\xFF])").unwrap(),
+            "\u{e9}".parse().unwrap(),
+            0,
+        );
+        file.rename(&latin1, RenameTarget::Stem).unwrap();
+        assert!(dir.path().join("caf\u{e9} menu.TXT").exists());
+    }
+
+    #[test]
+    fn test_compound_extensions() {
+        let compound = COMPOUND_EXTENSIONS.map(str::to_owned);
+        let part = |name: &str, target| {
+            let bytes = name.as_bytes();
+            Name::from_bytes(bytes.to_vec())
+                .range(target, &compound)
+                .and_then(|range| bytes.get(range).map(<[u8]>::to_vec))
+                .map(|found| String::from_utf8(found).unwrap())
+        };
+        for (name, stem, extension, extensions) in [
+            ("archive.tar.gz", "archive", Some("tar.gz"), Some("tar.gz")),
+            (
+                "db.backup.TAR.ZST",
+                "db.backup",
+                Some("TAR.ZST"),
+                Some("TAR.ZST"),
+            ),
+            (
+                "db.tar.gz.gpg",
+                "db.tar.gz",
+                Some("gpg"),
+                Some("tar.gz.gpg"),
+            ),
+            ("index.d.ts", "index", Some("d.ts"), Some("d.ts")),
+            ("photo.jpg", "photo", Some("jpg"), Some("jpg")),
+            // Dotted stems
+            ("v1.2.txt", "v1.2", Some("txt"), Some("txt")),
+            ("v1.2.final.txt", "v1.2.final", Some("txt"), Some("txt")),
+            ("photo.", "photo", Some(""), Some("")),
+            (".tar.gz", ".tar", Some("gz"), Some("gz")),
+            (".bashrc", ".bashrc", None, None),
+            ("README", "README", None, None),
+        ] {
+            assert_eq!(part(name, RenameTarget::Stem).as_deref(), Some(stem));
+            let parsed = Name::from_bytes(name.as_bytes().to_vec());
+            assert_eq!(parsed.stem(&compound), stem.as_bytes());
+            assert_eq!(
+                parsed.extension(&compound).as_deref(),
+                extension.map(str::as_bytes)
+            );
+            assert_eq!(
+                part(name, RenameTarget::Extension).as_deref(),
+                extension,
+                "{name}"
+            );
+            assert_eq!(
+                part(name, RenameTarget::Extensions).as_deref(),
+                extensions,
+                "{name}"
+            );
         }
+
+        // The boundary doesn't move when the replacement adds dots
+        let name = Name::from_bytes(b"archive.tar.gz".to_vec());
+        let extension =
+            name.to_renamed(RenameTarget::Extension, &compound, |_| {
+                b"tgz".to_vec()
+            });
+        assert_eq!(extension.to_string(), "archive.tgz");
+        let stem = name
+            .to_renamed(RenameTarget::Stem, &compound, |_| b"v1.0".to_vec());
+        assert_eq!(stem.to_string(), "v1.0.tar.gz");
+        assert_eq!(
+            Name::from_bytes(b"README".to_vec())
+                .to_renamed(RenameTarget::Extension, &compound, |_| {
+                    b"md".to_vec()
+                })
+                .to_string(),
+            "README"
+        );
+    }
+
+    #[test]
+    fn test_path_target() {
+        use regex::bytes::Regex;
+
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("2023").join("01");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(nested.join("photo.jpg"), "").unwrap();
+        let file = File::from_path(nested.join("photo.jpg")).unwrap();
+        assert_eq!(
+            file.relative_path(dir.path()),
+            Some(Path::new("2023").join("01").join("photo.jpg"))
+        );
+        assert_eq!(file.relative_path(&nested.join("other")), None);
+
+        let flatten = |with: &str| {
+            Strategy::new(Regex::new("/+").unwrap(), with.parse().unwrap(), 0)
+                .with_base(dir.path().to_path_buf())
+        };
+        assert_eq!(
+            file.renamed_path(&flatten("-"), RenameTarget::Path)
+                .unwrap(),
+            dir.path().join("2023-01-photo.jpg")
+        );
+        assert_eq!(
+            file.renamed_name(&flatten("-"), RenameTarget::Path)
+                .to_string(),
+            "2023-01-photo.jpg"
+        );
+        // Leaving the base directory isn't allowed
+        assert!(matches!(
+            file.needs_rename(&flatten("/../"), RenameTarget::Path),
+            Err(Error::LeavesBase(..))
+        ));
+        let other_dir = tempfile::tempdir().unwrap();
+        fs::write(other_dir.path().join("a"), "").unwrap();
+        let outside = File::from_path(other_dir.path().join("a")).unwrap();
+        assert!(matches!(
+            outside.renamed_path(&flatten("-"), RenameTarget::Path),
+            Err(Error::OutsideBase(..))
+        ));
+
+        // Other targets can't move files
+        let nested_name =
+            Strategy::new(Regex::new("o+").unwrap(), "/".parse().unwrap(), 0);
+        for target in [RenameTarget::Both, RenameTarget::Stem] {
+            assert!(matches!(
+                file.renamed_path(&nested_name, target),
+                Err(Error::Separator(..))
+            ));
+        }
+
+        let sorted = Strategy::new(
+            Regex::new(r"^(\d+)/(\d+)/").unwrap(),
+            "sorted/$1/$2-".parse().unwrap(),
+            0,
+        )
+        .with_base(dir.path().to_path_buf());
+        file.rename(&sorted, RenameTarget::Path).unwrap();
+        assert!(dir.path().join("sorted/2023/01-photo.jpg").is_file());
     }
 }
@@ -0,0 +1,491 @@
+/* Built-in imports */
+use core::time::Duration;
+use std::{
+    fs,
+    io::{self, Seek as _, SeekFrom, Write as _},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+/* Dependencies */
+use derive_more::{Display, FromStr};
+use serde::{Deserialize, Serialize};
+
+use crate::{file, os_bytes, tsv, File, Rename};
+
+/// Record of the renames done during a session, used to undo them.
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct Journal {
+    entries: Vec<Entry>,
+}
+
+/// A `from -> to` rename, along with the state of `to` right after it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Entry {
+    #[serde(with = "os_bytes::serde_path")]
+    from: PathBuf,
+    #[serde(with = "os_bytes::serde_path")]
+    to: PathBuf,
+    size: u64,
+    /// Modification time, since [`SystemTime::UNIX_EPOCH`].
+    modified: Duration,
+}
+
+impl Entry {
+    #[inline]
+    #[must_use]
+    pub const fn from(&self) -> &PathBuf {
+        &self.from
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn to(&self) -> &PathBuf {
+        &self.to
+    }
+
+    /// Entry of `rename`, once executed.
+    fn recorded(rename: &Rename) -> io::Result<Self> {
+        let (size, modified) = fingerprint(rename.to())?;
+        Ok(Self {
+            from: rename.from(),
+            to: rename.to().clone(),
+            size,
+            modified,
+        })
+    }
+
+    /// Moves the entry's paths into `dir` if they were inside `dir.from`.
+    fn relocated(self, dir: &Self) -> Self {
+        let relocate = |path: PathBuf| match path.strip_prefix(&dir.from) {
+            Ok(rest) if !rest.as_os_str().is_empty() => dir.to.join(rest),
+            _ => path,
+        };
+        Self {
+            from: relocate(self.from),
+            to: relocate(self.to),
+            ..self
+        }
+    }
+
+    /// Builds the rename reverting this entry.
+    ///
+    /// Fails if `to` is gone or doesn't look like the file
+    /// that was renamed anymore.
+    #[inline]
+    pub fn reversed(&self) -> Result<Rename, Error> {
+        if !self.to.exists() {
+            return Err(Error::Missing(self.to.clone()));
+        }
+        let (size, modified) = fingerprint(&self.to)?;
+        if size != self.size || modified != self.modified {
+            return Err(Error::Changed(self.to.clone()));
+        }
+
+        Ok(Rename::new(File::from_path(&self.to)?, self.from.clone()))
+    }
+}
+
+impl Journal {
+    #[inline]
+    #[must_use]
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// Records a rename that has been executed.
+    #[inline]
+    pub fn record(&mut self, rename: &Rename) -> io::Result<()> {
+        self.entries.push(Entry::recorded(rename)?);
+        Ok(())
+    }
+
+    /// Renames reverting the journal, latest first.
+    ///
+    /// Entries are relocated through the directory renames recorded after
+    /// them, so the content of a renamed directory gets reverted in place.
+    #[inline]
+    pub fn reversed(&self) -> impl Iterator<Item = Result<Rename, Error>> + '_ {
+        self.entries.iter().enumerate().rev().map(|(idx, entry)| {
+            self.entries
+                .iter()
+                .skip(idx + 1)
+                .fold(entry.clone(), Entry::relocated)
+                .reversed()
+        })
+    }
+
+    #[inline]
+    pub fn save<P: AsRef<Path>>(
+        &self,
+        path: P,
+        format: Format,
+    ) -> Result<(), Error> {
+        let content = match format {
+            Format::Json => serde_json::to_string_pretty(self)?,
+            Format::Tsv => self.to_tsv(),
+        };
+        Ok(fs::write(path, content)?)
+    }
+
+    #[inline]
+    pub fn load<P: AsRef<Path>>(
+        path: P,
+        format: Format,
+    ) -> Result<Self, Error> {
+        let content = fs::read_to_string(path)?;
+        match format {
+            Format::Json => Ok(serde_json::from_str(&content)?),
+            Format::Tsv => Self::from_tsv(&content),
+        }
+    }
+
+    fn to_tsv(&self) -> String {
+        self.entries.iter().map(tsv_line).fold(
+            format!("{TSV_HEADER}\n"),
+            |mut tsv, line| {
+                tsv.push_str(&line);
+                tsv
+            },
+        )
+    }
+
+    fn from_tsv(content: &str) -> Result<Self, Error> {
+        let entries = content
+            .lines()
+            .enumerate()
+            .filter(|&(_, line)| line != TSV_HEADER && !line.is_empty())
+            .map(|(idx, line)| {
+                let malformed = || Error::Malformed(idx + 1);
+                let mut fields = line.split('\t');
+                let mut next = || fields.next().ok_or_else(malformed);
+                let (from, to, size, modified) =
+                    (next()?, next()?, next()?, next()?);
+                let (secs, nanos) =
+                    modified.split_once('.').ok_or_else(malformed)?;
+
+                Ok(Entry {
+                    from: tsv::unescape_path(from).ok_or_else(malformed)?,
+                    to: tsv::unescape_path(to).ok_or_else(malformed)?,
+                    size: size.parse().map_err(|_err| malformed())?,
+                    modified: Duration::new(
+                        secs.parse().map_err(|_err| malformed())?,
+                        nanos.parse().map_err(|_err| malformed())?,
+                    ),
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+
+        Ok(Self { entries })
+    }
+}
+
+/// Journal saved as the renames get recorded, so that it is complete
+/// even if renaming is interrupted.
+#[derive(Debug)]
+pub struct Writer {
+    file: fs::File,
+    format: Format,
+    /// Entries written so far.
+    written: usize,
+}
+
+impl Writer {
+    /// Writes an empty journal at `path`, to fail before renaming anything
+    /// if it can't be.
+    ///
+    /// Note: an existing journal is never overwritten, its renames
+    /// couldn't be undone anymore.
+    #[inline]
+    pub fn create<P: AsRef<Path>>(
+        path: P,
+        format: Format,
+    ) -> Result<Self, Error> {
+        let journal_path = path.as_ref();
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(journal_path)
+            .map_err(|err| {
+                if err.kind() == io::ErrorKind::AlreadyExists {
+                    Error::Exists(journal_path.to_path_buf())
+                } else {
+                    Error::Io(err)
+                }
+            })?;
+        match format {
+            Format::Json => file.write_all(b"[\n]\n")?,
+            Format::Tsv => writeln!(file, "{TSV_HEADER}")?,
+        }
+        Ok(Self {
+            file,
+            format,
+            written: 0,
+        })
+    }
+
+    /// Appends a rename that has been executed.
+    ///
+    /// Note: JSON journals stay valid, their closing bracket being
+    /// written again after each entry.
+    #[inline]
+    pub fn record(&mut self, rename: &Rename) -> Result<(), Error> {
+        let entry = Entry::recorded(rename)?;
+        match self.format {
+            Format::Json => {
+                let json = serde_json::to_string(&entry)?;
+                if self.written == 0 {
+                    self.file.seek(SeekFrom::End(-2))?;
+                    write!(self.file, "  {json}\n]\n")?;
+                } else {
+                    self.file.seek(SeekFrom::End(-3))?;
+                    write!(self.file, ",\n  {json}\n]\n")?;
+                }
+            },
+            Format::Tsv => self.file.write_all(tsv_line(&entry).as_bytes())?,
+        }
+        self.file.flush()?;
+        self.written += 1;
+        Ok(())
+    }
+}
+
+const TSV_HEADER: &str = "from\tto\tsize\tmodified";
+
+fn tsv_line(entry: &Entry) -> String {
+    format!(
+        "{}\t{}\t{}\t{}.{:09}\n",
+        tsv::escape_path(&entry.from),
+        tsv::escape_path(&entry.to),
+        entry.size,
+        entry.modified.as_secs(),
+        entry.modified.subsec_nanos(),
+    )
+}
+
+/// Size and modification time of `path`, not following symlinks.
+///
+/// Note: directories change along with their content, so they all share
+/// the same fingerprint.
+fn fingerprint(path: &Path) -> io::Result<(u64, Duration)> {
+    let metadata = fs::symlink_metadata(path)?;
+    if metadata.is_dir() {
+        return Ok((0, Duration::ZERO));
+    }
+    let modified = metadata
+        .modified()?
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default();
+    Ok((metadata.len(), modified))
+}
+
+#[derive(Debug, Default, Display, Clone, Copy, FromStr, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Format {
+    Json,
+    #[default]
+    Tsv,
+}
+
+impl Format {
+    /// `.json` files are JSON journals, anything else is TSV.
+    #[inline]
+    #[must_use]
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Self {
+        match path.as_ref().extension() {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => Self::Json,
+            _ => Self::Tsv,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("{} no longer exists.", os_bytes::escape_path(.0))]
+    Missing(PathBuf),
+    #[error("{} has changed since it was renamed.", os_bytes::escape_path(.0))]
+    Changed(PathBuf),
+    #[error("The journal {} already exists.", os_bytes::escape_path(.0))]
+    Exists(PathBuf),
+    #[error("Malformed journal line {0}.")]
+    Malformed(usize),
+    #[error("{0}")]
+    File(#[from] file::Error),
+    #[error("{0}")]
+    Json(#[from] serde_json::Error),
+    #[error("io::Error: {0}")]
+    Io(#[from] io::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Plan;
+
+    fn journal(dir: &Path) -> Journal {
+        fs::write(dir.join("a"), "a").unwrap();
+        fs::write(dir.join("b\tc"), "b").unwrap();
+
+        let mut journal = Journal::default();
+        for (from, to) in [("a", "x"), ("b\tc", "y\\z")] {
+            let rename = Rename::new(
+                File::from_path(dir.join(from)).unwrap(),
+                dir.join(to),
+            );
+            fs::rename(rename.from(), rename.to()).unwrap();
+            journal.record(&rename).unwrap();
+        }
+        journal
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let journal = journal(dir.path());
+
+        for format in [Format::Json, Format::Tsv] {
+            let path = dir.path().join("journal");
+            journal.save(&path, format).unwrap();
+            assert_eq!(Journal::load(&path, format).unwrap(), journal);
+        }
+    }
+
+    #[test]
+    fn test_writer() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a"), "a").unwrap();
+        fs::write(dir.path().join("b"), "b").unwrap();
+        let renames = [("a", "x"), ("b", "y")].map(|(from, to)| {
+            Rename::new(
+                File::from_path(dir.path().join(from)).unwrap(),
+                dir.path().join(to),
+            )
+        });
+
+        for format in [Format::Json, Format::Tsv] {
+            let path = dir.path().join(format!("journal.{format}"));
+            let mut writer = Writer::create(&path, format).unwrap();
+            // Complete as soon as each rename is recorded
+            let mut expected = Journal::default();
+            assert_eq!(Journal::load(&path, format).unwrap(), expected);
+            for rename in &renames {
+                fs::rename(rename.from(), rename.to()).unwrap();
+                writer.record(rename).unwrap();
+                expected.record(rename).unwrap();
+                assert_eq!(Journal::load(&path, format).unwrap(), expected);
+                fs::rename(rename.to(), rename.from()).unwrap();
+            }
+            // Journals of previous runs are kept
+            let err = Writer::create(&path, format).unwrap_err();
+            assert!(matches!(err, Error::Exists(_)), "{err}");
+            assert_eq!(Journal::load(&path, format).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_non_utf8_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let latin1 = |name: &[u8]| {
+            dir.path().join(os_bytes::into_os_string(name.to_vec()))
+        };
+        fs::write(latin1(b"caf\xe9"), "").unwrap();
+        let rename = Rename::new(
+            File::from_path(latin1(b"caf\xe9")).unwrap(),
+            latin1(b"th\xe9"),
+        );
+        fs::rename(rename.from(), rename.to()).unwrap();
+        let mut journal = Journal::default();
+        journal.record(&rename).unwrap();
+
+        for format in [Format::Json, Format::Tsv] {
+            let path = dir.path().join("journal");
+            journal.save(&path, format).unwrap();
+            assert_eq!(Journal::load(&path, format).unwrap(), journal);
+        }
+    }
+
+    #[test]
+    fn test_malformed_tsv() {
+        for content in ["a\tb\t1", "a\tb\tx\t1.0", "a\\q\tb\t1\t1.0"] {
+            assert!(matches!(
+                Journal::from_tsv(content),
+                Err(Error::Malformed(1))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_undo() {
+        let dir = tempfile::tempdir().unwrap();
+        let journal = journal(dir.path());
+
+        let plan = journal.reversed().collect::<Result<Plan, _>>().unwrap();
+        assert!(plan.execute().all(|res| res.is_ok()));
+        assert_eq!(fs::read_to_string(dir.path().join("a")).unwrap(), "a");
+        assert_eq!(fs::read_to_string(dir.path().join("b\tc")).unwrap(), "b");
+    }
+
+    #[test]
+    fn test_undo_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("d")).unwrap();
+        fs::write(dir.path().join("d/a"), "a").unwrap();
+        let plan = Plan::from_iter([
+            Rename::new(
+                File::from_path(dir.path().join("d")).unwrap(),
+                dir.path().join("e"),
+            ),
+            Rename::new(
+                File::from_path(dir.path().join("d/a")).unwrap(),
+                dir.path().join("d/b"),
+            ),
+        ]);
+        let mut journal = Journal::default();
+        for rename in plan.execute() {
+            journal.record(rename.unwrap()).unwrap();
+        }
+        assert!(dir.path().join("e/b").exists());
+
+        let undo = journal.reversed().collect::<Result<Plan, _>>().unwrap();
+        assert!(undo.execute().all(|res| res.is_ok()));
+        assert_eq!(fs::read_to_string(dir.path().join("d/a")).unwrap(), "a");
+    }
+
+    #[test]
+    fn test_undo_refuses_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let journal = journal(dir.path());
+        fs::remove_file(dir.path().join("x")).unwrap();
+        fs::write(dir.path().join("y\\z"), "changed").unwrap();
+
+        let results = journal.reversed().collect::<Vec<_>>();
+        assert!(matches!(
+            results.as_slice(),
+            [Err(Error::Changed(_)), Err(Error::Missing(_))]
+        ));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_error_paths_are_escaped() {
+        let path = PathBuf::from(os_bytes::into_os_string(b"caf\xe9".to_vec()));
+        assert_eq!(
+            Error::Missing(path.clone()).to_string(),
+            "caf\\xE9 no longer exists."
+        );
+        assert_eq!(
+            Error::Changed(path).to_string(),
+            "caf\\xE9 has changed since it was renamed."
+        );
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(Format::from_path("journal.json"), Format::Json);
+        assert_eq!(Format::from_path("journal.JSON"), Format::Json);
+        assert_eq!(Format::from_path("journal.tsv"), Format::Tsv);
+        assert_eq!(Format::from_path("journal"), Format::Tsv);
+    }
+}
@@ -1,19 +1,313 @@
 /* Modules */
 mod file;
+pub mod journal;
+pub mod listing;
+pub mod mapping;
+mod metadata;
+#[cfg(feature = "clap")]
+pub mod options;
+pub mod os_bytes;
+pub mod plan;
+pub mod predicate;
+pub mod sanitize;
 mod sed_pattern;
+pub mod template;
 pub mod traits;
+pub mod tsv;
+pub mod walk;
+/* Built-in imports */
+use core::fmt;
+use std::path::{Path, PathBuf};
 /* Dependencies */
-use derive_more::{Constructor, Display};
-use regex::Regex;
+use derive_more::Display;
+use log::debug;
+use regex::bytes::{Captures, Regex};
 /* Re-exports */
-pub use file::{File, RenameTarget};
+pub use file::{File, RenameTarget, COMPOUND_EXTENSIONS};
+pub use journal::Journal;
+pub use metadata::Metadata;
+pub use plan::{Plan, Rename};
 pub use sed_pattern::SedPattern;
+pub use template::Template;
+/* Crate imports */
+use template::{Algorithm, Context, Digests, Sequence, Transform};
+#[cfg(feature = "media")]
+use template::{Tag, Tags};
 
-#[derive(Debug, Display, Constructor)]
+/// A single `pattern -> with` replacement of a [`Strategy`].
+#[derive(Debug, Display)]
 #[display("{pattern}\n{with}\n{limit}")]
-pub struct Strategy {
+struct Substitution {
     pattern: Regex,
-    with: String,
-    /// 0 means all
+    with: Template,
+    /// 0 means all.
     limit: usize,
 }
+
+/// Replacements applied in order to file names.
+#[derive(Debug)]
+pub struct Strategy {
+    substitutions: Vec<Substitution>,
+    sequence: Sequence,
+    digests: Digests,
+    #[cfg(feature = "media")]
+    tags: Tags,
+    /// Extensions kept whole when renaming extensions or stems.
+    compound: Vec<String>,
+    /// Directory paths are relative to, see [`RenameTarget::Path`].
+    base: PathBuf,
+}
+
+impl fmt::Display for Strategy {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, substitution) in self.substitutions.iter().enumerate() {
+            if idx > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{substitution}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Strategy {
+    #[inline]
+    #[must_use]
+    pub fn new(pattern: Regex, with: Template, limit: usize) -> Self {
+        Self {
+            substitutions: vec![Substitution {
+                pattern,
+                with,
+                limit,
+            }],
+            sequence: Sequence::default(),
+            digests: Digests::default(),
+            #[cfg(feature = "media")]
+            tags: Tags::default(),
+            compound: COMPOUND_EXTENSIONS.map(str::to_owned).to_vec(),
+            base: PathBuf::from("."),
+        }
+    }
+
+    /// Chains another replacement, applied to the result of the previous ones.
+    #[inline]
+    #[must_use]
+    pub fn then(
+        mut self,
+        pattern: Regex,
+        with: Template,
+        limit: usize,
+    ) -> Self {
+        self.substitutions.push(Substitution {
+            pattern,
+            with,
+            limit,
+        });
+        self
+    }
+
+    /// Chains `transform` applied to the whole result, e.g. to normalize it.
+    #[inline]
+    #[must_use]
+    pub fn then_transform(self, transform: Transform) -> Self {
+        let (pattern, with, limit) = transform.export();
+        self.then(pattern, with, limit)
+    }
+
+    /// Numbers files according to `sequence` for `{n}` placeholders.
+    #[inline]
+    #[must_use]
+    pub fn with_sequence(self, sequence: Sequence) -> Self {
+        Self { sequence, ..self }
+    }
+
+    /// Uses precomputed `digests` for `{hash}` placeholders.
+    #[inline]
+    #[must_use]
+    pub fn with_digests(self, digests: Digests) -> Self {
+        Self { digests, ..self }
+    }
+
+    /// Uses tags read ahead for media placeholders.
+    #[cfg(feature = "media")]
+    #[inline]
+    #[must_use]
+    pub fn with_tags(self, tags: Tags) -> Self {
+        Self { tags, ..self }
+    }
+
+    /// Treats the `compound` extensions, like `tar.gz`, as a single one.
+    #[inline]
+    #[must_use]
+    pub fn with_compound_extensions(self, compound: Vec<String>) -> Self {
+        Self { compound, ..self }
+    }
+
+    /// Extensions renamed as a whole, see [`RenameTarget`].
+    #[inline]
+    #[must_use]
+    pub fn compound_extensions(&self) -> &[String] {
+        &self.compound
+    }
+
+    /// Renames paths relative to `base`, the current directory by default.
+    #[inline]
+    #[must_use]
+    pub fn with_base(self, base: PathBuf) -> Self {
+        Self { base, ..self }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn base(&self) -> &Path {
+        &self.base
+    }
+
+    /// Whether any of the patterns matches `text`, the replacements leaving
+    /// it untouched otherwise.
+    #[inline]
+    #[must_use]
+    pub fn is_match(&self, text: &[u8]) -> bool {
+        self.substitutions
+            .iter()
+            .any(|substitution| substitution.pattern.is_match(text))
+    }
+
+    /// Algorithms used by `{hash}` placeholders, to compute digests ahead.
+    #[inline]
+    #[must_use]
+    pub fn algorithms(&self) -> Vec<Algorithm> {
+        let mut algorithms = Vec::new();
+        for algorithm in self
+            .substitutions
+            .iter()
+            .flat_map(|substitution| substitution.with.algorithms())
+        {
+            if !algorithms.contains(&algorithm) {
+                algorithms.push(algorithm);
+            }
+        }
+        algorithms
+    }
+
+    /// Tags used by media placeholders, to read them ahead.
+    #[cfg(feature = "media")]
+    #[inline]
+    #[must_use]
+    pub fn tags(&self) -> Vec<Tag> {
+        let mut tags = Vec::new();
+        for tag in self
+            .substitutions
+            .iter()
+            .flat_map(|substitution| substitution.with.tags())
+        {
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        tags
+    }
+
+    /// Replaces matches of the patterns in `text`, part of `file`'s name.
+    ///
+    /// Intermediate results of chained replacements are logged.
+    #[inline]
+    #[must_use]
+    pub fn replace(&self, text: &[u8], file: &File) -> Vec<u8> {
+        let ctx = Context {
+            file,
+            counter: self.sequence.number(file),
+            digests: &self.digests,
+            #[cfg(feature = "media")]
+            tags: &self.tags,
+        };
+        let chained = self.substitutions.len() > 1;
+        self.substitutions.iter().zip(1_usize..).fold(
+            text.to_vec(),
+            |current, (substitution, step)| {
+                let replaced = substitution
+                    .pattern
+                    .replacen(
+                        &current,
+                        substitution.limit,
+                        |caps: &Captures| substitution.with.render(caps, &ctx),
+                    )
+                    .into_owned();
+                if chained {
+                    debug!(
+                        "{}: {:?} -> {:?} (step {step})",
+                        os_bytes::escape_path(&file.path()),
+                        os_bytes::escape(&current),
+                        os_bytes::escape(&replaced),
+                    );
+                }
+                replaced
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_chained_replacements() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("My Holiday 2024.JPG"), "").unwrap();
+        let file =
+            File::from_path(dir.path().join("My Holiday 2024.JPG")).unwrap();
+        let strategy =
+            Strategy::new(Regex::new(" +").unwrap(), "_".parse().unwrap(), 0)
+                .then(Regex::new(".+").unwrap(), r"\L$0".parse().unwrap(), 0)
+                .then(
+                    Regex::new(r"_(\d+)").unwrap(),
+                    "-$1".parse().unwrap(),
+                    1,
+                );
+
+        assert_eq!(
+            file.renamed_name(&strategy, RenameTarget::Both).to_string(),
+            "my_holiday-2024.jpg"
+        );
+        assert_eq!(strategy.to_string().lines().count(), 9);
+    }
+
+    #[test]
+    fn test_then_transform() {
+        let dir = tempfile::tempdir().unwrap();
+        let decomposed = "Cafe\u{301} Cre\u{300}me.txt";
+        fs::write(dir.path().join(decomposed), "").unwrap();
+        let file = File::from_path(dir.path().join(decomposed)).unwrap();
+        let strategy =
+            Strategy::new(Regex::new(" +").unwrap(), "_".parse().unwrap(), 0)
+                .then_transform(Transform::Nfc);
+        assert_eq!(
+            file.renamed_name(&strategy, RenameTarget::Both).to_string(),
+            "Caf\u{e9}_Cr\u{e8}me.txt"
+        );
+
+        let (pattern, with, _) = Transform::Ascii.export();
+        let ascii = Strategy::new(pattern, with, 0);
+        assert_eq!(
+            ascii.replace(b"\xff-\xc3\xa9t\xc3\xa9\xff", &file),
+            b"\xff-ete\xff"
+        );
+    }
+
+    #[test]
+    fn test_matches() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("photo 1.jpg"), "").unwrap();
+        let file = File::from_path(dir.path().join("photo 1.jpg")).unwrap();
+        // Chained patterns only see what the previous ones produced
+        let strategy =
+            Strategy::new(Regex::new("x+").unwrap(), "y".parse().unwrap(), 0)
+                .then(Regex::new(" +").unwrap(), "_".parse().unwrap(), 0);
+        assert!(file.matches(&strategy, RenameTarget::Both));
+        assert!(file.matches(&strategy, RenameTarget::Stem));
+        assert!(!file.matches(&strategy, RenameTarget::Extension));
+    }
+}
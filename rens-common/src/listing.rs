@@ -0,0 +1,176 @@
+//! Numbered list of paths, edited by hand to rename them.
+//!
+//! Each line holds the number of a file and its path, as a [`tsv`] field.
+//! Lines can be reordered, but every file must be listed exactly once.
+
+/* Built-in imports */
+use std::{collections::HashSet, path::PathBuf};
+/* Crate imports */
+use crate::{os_bytes, tsv, File, Rename};
+
+const HEADER: &str = "# Edit the paths to rename the files, \
+                      keep the numbers and don't remove any line.";
+
+/// Lists `files`, numbered from 1.
+#[inline]
+#[must_use]
+pub fn write(files: &[File]) -> String {
+    let width = files.len().to_string().len();
+    files
+        .iter()
+        .zip(1_usize..)
+        .map(|(file, id)| {
+            format!("{id:0width$}\t{}\n", tsv::escape_path(&file.path()))
+        })
+        .fold(format!("{HEADER}\n"), |mut listing, line| {
+            listing.push_str(&line);
+            listing
+        })
+}
+
+/// Renames turning `files` into the edited `listing`.
+///
+/// Blank lines and lines starting with `#` are ignored,
+/// unchanged paths don't produce any rename.
+#[inline]
+pub fn parse(files: &[File], listing: &str) -> Result<Vec<Rename>, Error> {
+    let mut seen = HashSet::new();
+    let mut renames = Vec::new();
+    for (idx, line) in listing.lines().enumerate() {
+        let line_nb = idx + 1;
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (id, path) = line
+            .split_once('\t')
+            .and_then(|(id, path)| {
+                Some((id.trim().parse::<usize>().ok()?, path))
+            })
+            .ok_or(Error::Malformed(line_nb))?;
+        let to = tsv::unescape_path(path)
+            .filter(|unescaped| !unescaped.as_os_str().is_empty())
+            .ok_or(Error::Malformed(line_nb))?;
+        let file = id
+            .checked_sub(1)
+            .and_then(|pos| files.get(pos))
+            .ok_or(Error::Unknown(line_nb, id))?;
+        if !seen.insert(id) {
+            return Err(Error::Duplicated(id));
+        }
+        if to != file.path() {
+            renames.push(Rename::new(file.clone(), to));
+        }
+    }
+
+    if let Some(file) = files
+        .iter()
+        .zip(1_usize..)
+        .find_map(|(file, id)| (!seen.contains(&id)).then_some(file))
+    {
+        return Err(Error::Deleted(file.path()));
+    }
+    Ok(renames)
+}
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("Line {0} isn't a number followed by a tab and a path.")]
+    Malformed(usize),
+    #[error("Line {0} refers to unknown file #{1}.")]
+    Unknown(usize, usize),
+    #[error("File #{0} is listed more than once.")]
+    Duplicated(usize),
+    #[error("The line of {} was deleted.", os_bytes::escape_path(.0))]
+    Deleted(PathBuf),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, path::Path};
+
+    fn files(dir: &Path) -> Vec<File> {
+        ["a", "b\tc", "d"]
+            .iter()
+            .map(|name| {
+                fs::write(dir.join(name), "").unwrap();
+                File::from_path(dir.join(name)).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let files = files(dir.path());
+        let listing = write(&files);
+
+        assert!(listing.contains("\n2\t"));
+        assert!(listing.contains("b\\tc\n"));
+        assert_eq!(parse(&files, &listing), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn test_edits() {
+        let dir = tempfile::tempdir().unwrap();
+        let files = files(dir.path());
+        let listing = write(&files)
+            .replace("b\\tc", "e\\nf")
+            .replace(dir.path().join("d").to_string_lossy().as_ref(), "sub/d")
+            .lines()
+            .rev()
+            .collect::<Vec<_>>()
+            .join("\n\n");
+
+        let renames = parse(&files, &listing).unwrap();
+        let moved = renames
+            .iter()
+            .map(|rename| {
+                (rename.from(), rename.to().to_string_lossy().into_owned())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            moved,
+            [
+                (dir.path().join("d"), "sub/d".to_owned()),
+                (
+                    dir.path().join("b\tc"),
+                    dir.path().join("e\nf").to_string_lossy().into_owned()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_edits() {
+        let dir = tempfile::tempdir().unwrap();
+        let files = files(dir.path());
+        let listing = write(&files);
+        let mut lines = listing.lines().skip(1).map(str::to_owned);
+        let (first, second, third) = (
+            lines.next().unwrap(),
+            lines.next().unwrap(),
+            lines.next().unwrap(),
+        );
+        let parsed = |kept: &[&str]| parse(&files, &kept.join("\n"));
+
+        assert_eq!(
+            parsed(&[&first, &third]),
+            Err(Error::Deleted(dir.path().join("b\tc")))
+        );
+        assert_eq!(
+            parsed(&[&first, &second, &third, &first]),
+            Err(Error::Duplicated(1))
+        );
+        assert_eq!(
+            parsed(&[&first, &second, &third, "4\te"]),
+            Err(Error::Unknown(4, 4))
+        );
+        assert_eq!(
+            parsed(&[&first, "no id", &third]),
+            Err(Error::Malformed(2))
+        );
+        assert_eq!(parsed(&[&first, "2\t", &third]), Err(Error::Malformed(2)));
+    }
+}
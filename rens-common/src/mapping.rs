@@ -0,0 +1,231 @@
+//! Explicit `from -> to` pairs, as written by spreadsheets or scripts.
+//!
+//! CSV and TSV files hold a `from` and a `to` column, optionally preceded by
+//! a `from`/`to` (or `old`/`new`) header. TSV fields use [`tsv`] escaping,
+//! like the TSV output of a dry run.
+//! JSON files hold an array of `{"from": .., "to": ..}` objects.
+
+/* Built-in imports */
+use std::{
+    ffi::OsStr,
+    fs, io,
+    path::{Path, PathBuf},
+};
+/* Crate imports */
+use crate::{os_bytes, tsv};
+/* Dependencies */
+use derive_more::{Display, FromStr};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+pub struct Pair {
+    #[serde(
+        alias = "old",
+        deserialize_with = "os_bytes::serde_path::deserialize"
+    )]
+    from: PathBuf,
+    #[serde(
+        alias = "new",
+        deserialize_with = "os_bytes::serde_path::deserialize"
+    )]
+    to: PathBuf,
+}
+
+impl Pair {
+    #[inline]
+    #[must_use]
+    pub const fn from(&self) -> &PathBuf {
+        &self.from
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn to(&self) -> &PathBuf {
+        &self.to
+    }
+
+    /// Builds a pair from the fields of a record, `None` if it's a header.
+    fn from_record<S: AsRef<OsStr>>(
+        record: &[S],
+        line: usize,
+    ) -> Result<Option<Self>, Error> {
+        let mut fields = record.iter().map(AsRef::as_ref);
+        let (Some(from), Some(to)) = (fields.next(), fields.next()) else {
+            return Err(Error::Malformed(line));
+        };
+        let is_header = line == 1
+            && ((from.eq_ignore_ascii_case("from")
+                && to.eq_ignore_ascii_case("to"))
+                || (from.eq_ignore_ascii_case("old")
+                    && to.eq_ignore_ascii_case("new")));
+        if is_header {
+            return Ok(None);
+        }
+        if from.is_empty() || to.is_empty() {
+            return Err(Error::Malformed(line));
+        }
+        Ok(Some(Self {
+            from: from.into(),
+            to: to.into(),
+        }))
+    }
+}
+
+#[derive(Debug, Default, Display, Clone, Copy, FromStr, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Format {
+    #[default]
+    Csv,
+    Tsv,
+    Json,
+}
+
+impl Format {
+    /// Guesses the format from the extension, defaulting to CSV.
+    #[inline]
+    #[must_use]
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Self {
+        match path.as_ref().extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => Self::Json,
+            Some(ext)
+                if ext.eq_ignore_ascii_case("tsv")
+                    || ext.eq_ignore_ascii_case("tab") =>
+            {
+                Self::Tsv
+            },
+            _ => Self::Csv,
+        }
+    }
+}
+
+#[inline]
+pub fn load<P: AsRef<Path>>(
+    path: P,
+    format: Format,
+) -> Result<Vec<Pair>, Error> {
+    parse(&fs::read_to_string(path)?, format)
+}
+
+#[inline]
+pub fn parse(content: &str, format: Format) -> Result<Vec<Pair>, Error> {
+    match format {
+        Format::Json => Ok(serde_json::from_str(content)?),
+        Format::Tsv => content
+            .lines()
+            .zip(1..)
+            .filter(|&(line, _)| !line.is_empty())
+            .map(|(line, nb)| {
+                let fields = line
+                    .split('\t')
+                    .map(tsv::unescape_path)
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or(Error::Malformed(nb))?;
+                Pair::from_record(&fields, nb)
+            })
+            .filter_map(Result::transpose)
+            .collect(),
+        Format::Csv => csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(content.as_bytes())
+            .into_records()
+            .zip(1..)
+            .map(|(record, nb)| {
+                Pair::from_record(&record?.iter().collect::<Vec<_>>(), nb)
+            })
+            .filter_map(Result::transpose)
+            .collect(),
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("Line {0} doesn't hold a `from` and a `to` path.")]
+    Malformed(usize),
+    #[error("{0}")]
+    Csv(#[from] csv::Error),
+    #[error("{0}")]
+    Json(#[from] serde_json::Error),
+    #[error("io::Error: {0}")]
+    Io(#[from] io::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(content: &str, format: Format) -> Vec<(String, String)> {
+        parse(content, format)
+            .unwrap()
+            .iter()
+            .map(|pair| {
+                (
+                    pair.from().to_string_lossy().into_owned(),
+                    pair.to().to_string_lossy().into_owned(),
+                )
+            })
+            .collect()
+    }
+
+    fn expected() -> Vec<(String, String)> {
+        vec![
+            ("a b.txt".to_owned(), "c,d.txt".to_owned()),
+            ("e\tf".to_owned(), "dir/g".to_owned()),
+        ]
+    }
+
+    #[test]
+    fn test_csv() {
+        let content = "old,new\na b.txt,\"c,d.txt\"\n\"e\tf\",dir/g,ignored\n";
+        assert_eq!(pairs(content, Format::Csv), expected());
+        assert_eq!(
+            pairs(&content.replacen("old,new\n", "", 1), Format::Csv),
+            expected()
+        );
+    }
+
+    #[test]
+    fn test_tsv() {
+        let content =
+            "from\tto\tconflict\na b.txt\tc,d.txt\t\n\ne\\tf\tdir/g\n";
+        assert_eq!(pairs(content, Format::Tsv), expected());
+    }
+
+    #[test]
+    fn test_json() {
+        let content = r#"[
+            {"from": "a b.txt", "to": "c,d.txt", "conflict": null},
+            {"old": "e\tf", "new": "dir/g"}
+        ]"#;
+        assert_eq!(pairs(content, Format::Json), expected());
+    }
+
+    #[test]
+    fn test_malformed() {
+        assert!(matches!(
+            parse("a.txt,b.txt\nc.txt\n", Format::Csv),
+            Err(Error::Malformed(2))
+        ));
+        assert!(matches!(
+            parse("a\\q\tb\n", Format::Tsv),
+            Err(Error::Malformed(1))
+        ));
+        assert!(matches!(
+            parse("a\t\n", Format::Tsv),
+            Err(Error::Malformed(1))
+        ));
+        assert!(matches!(
+            parse(r#"[{"from": "a"}]"#, Format::Json),
+            Err(Error::Json(_))
+        ));
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(Format::from_path("renames.json"), Format::Json);
+        assert_eq!(Format::from_path("renames.TSV"), Format::Tsv);
+        assert_eq!(Format::from_path("renames.csv"), Format::Csv);
+        assert_eq!(Format::from_path("renames"), Format::Csv);
+    }
+}
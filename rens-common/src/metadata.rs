@@ -0,0 +1,122 @@
+/* Built-in imports */
+#[cfg(unix)]
+use core::time::Duration;
+#[cfg(unix)]
+use std::os::unix::fs::MetadataExt as _;
+use std::{fs, time::SystemTime};
+
+/// Metadata of a [`crate::File`], gathered when it is found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Metadata {
+    size: u64,
+    modified: Option<SystemTime>,
+    /// Status change time on unix, creation time elsewhere.
+    changed: Option<SystemTime>,
+    accessed: Option<SystemTime>,
+    #[cfg(unix)]
+    uid: u32,
+    #[cfg(unix)]
+    gid: u32,
+    #[cfg(unix)]
+    mode: u32,
+}
+
+impl Metadata {
+    #[inline]
+    #[must_use]
+    pub const fn size(&self) -> u64 {
+        self.size
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn modified(&self) -> Option<SystemTime> {
+        self.modified
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn changed(&self) -> Option<SystemTime> {
+        self.changed
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn accessed(&self) -> Option<SystemTime> {
+        self.accessed
+    }
+
+    /// Name of the owner, or its uid if it has none.
+    #[inline]
+    #[must_use]
+    pub fn owner(&self) -> Option<String> {
+        #[cfg(unix)]
+        return Some(uzers::get_user_by_uid(self.uid).map_or_else(
+            || self.uid.to_string(),
+            |user| user.name().to_string_lossy().into_owned(),
+        ));
+        #[cfg(not(unix))]
+        None
+    }
+
+    /// Id of the owner.
+    #[inline]
+    #[must_use]
+    pub const fn uid(&self) -> Option<u32> {
+        #[cfg(unix)]
+        return Some(self.uid);
+        #[cfg(not(unix))]
+        None
+    }
+
+    /// Name of the owning group, or its gid if it has none.
+    #[inline]
+    #[must_use]
+    pub fn group(&self) -> Option<String> {
+        #[cfg(unix)]
+        return Some(uzers::get_group_by_gid(self.gid).map_or_else(
+            || self.gid.to_string(),
+            |group| group.name().to_string_lossy().into_owned(),
+        ));
+        #[cfg(not(unix))]
+        None
+    }
+
+    /// Permission bits, like `644`.
+    #[inline]
+    #[must_use]
+    pub fn mode(&self) -> Option<String> {
+        #[cfg(unix)]
+        return Some(format!("{:o}", self.mode & 0o7777));
+        #[cfg(not(unix))]
+        None
+    }
+}
+
+impl From<&fs::Metadata> for Metadata {
+    #[inline]
+    fn from(metadata: &fs::Metadata) -> Self {
+        #[cfg(unix)]
+        let changed = u64::try_from(metadata.ctime())
+            .ok()
+            .zip(u32::try_from(metadata.ctime_nsec()).ok())
+            .and_then(|(secs, nanos)| {
+                SystemTime::UNIX_EPOCH.checked_add(Duration::new(secs, nanos))
+            });
+        #[cfg(not(unix))]
+        let changed = metadata.created().ok();
+
+        Self {
+            size: metadata.len(),
+            modified: metadata.modified().ok(),
+            changed,
+            accessed: metadata.accessed().ok(),
+            #[cfg(unix)]
+            uid: metadata.uid(),
+            #[cfg(unix)]
+            gid: metadata.gid(),
+            #[cfg(unix)]
+            mode: metadata.mode(),
+        }
+    }
+}
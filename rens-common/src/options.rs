@@ -0,0 +1,29 @@
+//! Command line options shared by the `rens` front-ends.
+
+/* Modules */
+pub mod git;
+pub mod pattern;
+pub mod recursion;
+pub mod selection;
+/* Built-in imports */
+use std::{io, path::PathBuf};
+
+/// Parses the path of an existing file or directory.
+///
+/// # Errors
+///
+/// Fails if nothing exists at `input`, or it can't be canonicalized.
+#[inline]
+pub fn path_exists(input: &str) -> io::Result<PathBuf> {
+    let path: PathBuf = input.into();
+    if path.exists() {
+        // simply ensure the path is canonicalizable
+        dunce::canonicalize(&path)?;
+        Ok(path)
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("No such file or directory: '{input}'."),
+        ))
+    }
+}
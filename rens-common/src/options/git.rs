@@ -1,9 +1,12 @@
 /* Dependencies */
 use clap::{ArgAction, Args};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Args)]
+#[derive(Debug, Default, Clone, Args, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 #[group(id = "git_options")]
 #[command(next_help_heading = "Git integration Options", display_order = 0)]
+#[non_exhaustive]
 pub struct Options {
     #[arg(
         name = "ignore",
@@ -12,13 +15,14 @@ pub struct Options {
         action = ArgAction::SetTrue,
     )]
     /// Parse and follow `.gitignore` (local and global), `.ignore` and `.git/info/exclude` files.
+    #[serde(rename = "ignore")]
     pub auto_ignore: bool,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use clap::{CommandFactory, Parser};
+    use clap::{CommandFactory as _, Parser};
 
     #[derive(Debug, Parser)]
     struct TestParser {
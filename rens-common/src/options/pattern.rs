@@ -2,10 +2,16 @@
 use core::num::NonZeroUsize;
 /* Dependencies */
 use clap::Args;
+use regex::bytes::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Args)]
+#[derive(
+    Debug, Default, Clone, PartialEq, Eq, Args, Serialize, Deserialize,
+)]
+#[serde(default, deny_unknown_fields)]
 #[group(id = "pattern_options")]
 #[command(next_help_heading = "Pattern Options")]
+#[non_exhaustive]
 pub struct Options {
     /// Wether or not the pattern should be made case sensitive.
     ///
@@ -19,10 +25,31 @@ pub struct Options {
     pub occurence: Option<NonZeroUsize>,
 }
 
+impl Options {
+    /// Applies the options to `regex`, returning it with the replacement limit.
+    #[inline]
+    pub fn apply(&self, regex: Regex) -> (Regex, usize) {
+        let limit = self.occurence.map_or(0, usize::from);
+        if self.case_insensitive {
+            (to_regex_case_insensitive(&regex), limit)
+        } else {
+            (regex, limit)
+        }
+    }
+}
+
+#[allow(clippy::expect_used)]
+fn to_regex_case_insensitive(regex: &Regex) -> Regex {
+    RegexBuilder::new(regex.as_str())
+        .case_insensitive(true)
+        .build()
+        .expect("Failed to build case insensitive Regex.")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use clap::{CommandFactory, Parser};
+    use clap::{CommandFactory as _, Parser};
 
     #[derive(Debug, Parser)]
     struct TestParser {
@@ -1,8 +1,11 @@
 /* Dependencies */
 use clap::{builder::ArgPredicate, ArgAction, Args};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Args)]
+#[derive(Debug, Default, Clone, Args, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 #[command(next_help_heading = "Recursion Options")]
+#[non_exhaustive]
 pub struct Recursion {
     /// When traversing directories, include hidden files.
     #[arg(
@@ -32,7 +35,7 @@ pub struct Recursion {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use clap::{CommandFactory, Parser};
+    use clap::{CommandFactory as _, Parser};
 
     #[derive(Debug, Parser)]
     struct TestParser {
@@ -0,0 +1,289 @@
+/* Built-in imports */
+use std::{path::Path, time::SystemTime};
+/* Crate imports */
+use crate::{
+    predicate::{self, Predicates},
+    traits::FileKind,
+    walk::Filter,
+};
+/* Dependencies */
+use clap::{ArgAction, Args, ValueEnum};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Clone, Args, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+#[command(next_help_heading = "Selection Options")]
+#[non_exhaustive]
+pub struct Selection {
+    /// Rename directories as well as files.
+    ///
+    /// Note: directories are renamed after their content.
+    #[arg(
+        long,
+        default_value_t = false,
+        conflicts_with = "only_dirs",
+        action = ArgAction::SetTrue,
+    )]
+    pub include_dirs: bool,
+
+    /// Rename directories only.
+    #[arg(
+        long,
+        default_value_t = false,
+        action = ArgAction::SetTrue,
+    )]
+    pub only_dirs: bool,
+
+    /// Only rename the paths matching this glob, can be repeated.
+    ///
+    /// Note: globs follow the `.gitignore` syntax, relative to the current directory.
+    #[arg(long, value_name = "glob")]
+    pub include: Vec<String>,
+
+    /// Don't rename nor traverse the paths matching this glob, can be repeated.
+    #[arg(long, value_name = "glob")]
+    pub exclude: Vec<String>,
+
+    /// Only rename the paths matching this regex.
+    #[arg(long, value_name = "regex", value_parser = valid_regex)]
+    pub match_path: Option<String>,
+
+    /// Only rename this kind of file, can be repeated.
+    ///
+    /// Note: `dir` implies --include-dirs.
+    #[arg(long = "type", value_enum, value_name = "kind")]
+    pub types: Vec<Type>,
+
+    /// Only rename the files modified after this date, or within this duration.
+    ///
+    /// Note: dates look like `2024-01-31` or `2024-01-31 12:00:00` (local time),
+    /// durations like `90m`, `1d12h` or `2w` [units: s, m, h, d, w].
+    #[arg(long, value_name = "date|duration", value_parser = valid_time)]
+    pub newer_than: Option<String>,
+
+    /// Only rename the files modified before this date, or this long ago.
+    #[arg(long, value_name = "date|duration", value_parser = valid_time)]
+    pub older_than: Option<String>,
+
+    /// Only rename the files weighing at least this size, like `10k` or `4M`.
+    #[arg(long, value_name = "size", value_parser = predicate::parse_size)]
+    pub min_size: Option<u64>,
+
+    /// Only rename the files weighing at most this size, like `10k` or `4M`.
+    #[arg(long, value_name = "size", value_parser = predicate::parse_size)]
+    pub max_size: Option<u64>,
+
+    /// Only rename the files owned by this user (name or uid).
+    #[arg(long, value_name = "user", value_parser = valid_owner)]
+    pub owner: Option<String>,
+}
+
+#[derive(
+    Debug, Clone, Copy, ValueEnum, PartialEq, Eq, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum Type {
+    File,
+    Dir,
+    Symlink,
+}
+
+impl From<Type> for FileKind {
+    #[inline]
+    fn from(kind: Type) -> Self {
+        match kind {
+            Type::File => Self::File,
+            Type::Dir => Self::Directory,
+            Type::Symlink => Self::Symlink,
+        }
+    }
+}
+
+fn valid_time(input: &str) -> Result<String, predicate::Error> {
+    predicate::parse_time(input, SystemTime::now()).map(|_| input.to_owned())
+}
+
+fn valid_owner(input: &str) -> Result<String, predicate::Error> {
+    predicate::parse_owner(input).map(|_| input.to_owned())
+}
+
+fn valid_regex(input: &str) -> Result<String, regex::Error> {
+    Regex::new(input).map(|_| input.to_owned())
+}
+
+impl Selection {
+    #[inline]
+    #[must_use]
+    pub fn dirs(&self) -> bool {
+        self.include_dirs || self.only_dirs || self.types.contains(&Type::Dir)
+    }
+
+    /// Predicates on the metadata, durations going back from `now`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if a date or duration is invalid, or the owner unknown.
+    #[inline]
+    pub fn predicates(
+        &self,
+        now: SystemTime,
+    ) -> Result<Predicates, predicate::Error> {
+        let time = |input: Option<&str>| {
+            input
+                .map(|time| predicate::parse_time(time, now))
+                .transpose()
+        };
+        Ok(Predicates::new(
+            time(self.newer_than.as_deref())?,
+            time(self.older_than.as_deref())?,
+            self.min_size,
+            self.max_size,
+            self.owner
+                .as_deref()
+                .map(predicate::parse_owner)
+                .transpose()?,
+        ))
+    }
+
+    /// Filter for the walker, globs being relative to `root`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if a glob or the path regex is invalid.
+    #[inline]
+    pub fn filter(&self, root: &Path) -> Result<Filter, Error> {
+        let filter = Filter::new(root, &self.include, &self.exclude)?
+            .with_kinds(
+                self.types.iter().copied().map(FileKind::from).collect(),
+            );
+        Ok(match self.match_path.as_deref().map(Regex::new) {
+            Some(regex) => filter.with_path_regex(regex?),
+            None => filter,
+        })
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn matches(&self, kind: FileKind) -> bool {
+        if kind == FileKind::Directory {
+            self.dirs()
+        } else {
+            !self.only_dirs
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error(transparent)]
+    Glob(#[from] ignore::Error),
+    #[error(transparent)]
+    Regex(#[from] regex::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{CommandFactory as _, Parser};
+    use core::time::Duration;
+
+    #[derive(Debug, Parser)]
+    struct TestParser {
+        #[command(flatten)]
+        pub options: Selection,
+    }
+
+    #[test]
+    fn selection_options_are_valid() {
+        TestParser::command().debug_assert();
+    }
+
+    #[test]
+    fn test_default_values() {
+        let args = TestParser::parse_from::<[_; 0], &str>([]);
+
+        assert!(!args.options.dirs());
+        assert!(args.options.matches(FileKind::File));
+        assert!(args.options.matches(FileKind::Symlink));
+        assert!(!args.options.matches(FileKind::Directory));
+    }
+
+    #[test]
+    fn test_include_dirs() {
+        let args = TestParser::parse_from(["rens-cli", "--include-dirs"]);
+
+        assert!(args.options.matches(FileKind::File));
+        assert!(args.options.matches(FileKind::Directory));
+    }
+
+    #[test]
+    fn test_only_dirs() {
+        let args = TestParser::parse_from(["rens-cli", "--only-dirs"]);
+
+        assert!(!args.options.matches(FileKind::File));
+        assert!(args.options.matches(FileKind::Directory));
+
+        TestParser::try_parse_from([
+            "rens-cli",
+            "--only-dirs",
+            "--include-dirs",
+        ])
+        .unwrap_err();
+    }
+
+    #[test]
+    fn test_filters() {
+        let args = TestParser::parse_from([
+            "rens-cli",
+            "--include=*.jpg",
+            "--include",
+            "*.png",
+            "--exclude=cache/",
+            "--match-path=^photos",
+            "--type=file",
+            "--type=dir",
+        ]);
+
+        assert_eq!(args.options.include, ["*.jpg", "*.png"]);
+        assert_eq!(args.options.exclude, ["cache/"]);
+        assert_eq!(args.options.match_path.as_deref(), Some("^photos"));
+        assert_eq!(args.options.types, [Type::File, Type::Dir]);
+        assert!(args.options.matches(FileKind::Directory));
+
+        TestParser::try_parse_from(["rens-cli", "--match-path=("]).unwrap_err();
+        TestParser::try_parse_from(["rens-cli", "--type=socket"]).unwrap_err();
+    }
+
+    #[test]
+    fn test_predicates() {
+        let args = TestParser::parse_from([
+            "rens-cli",
+            "--newer-than=2024-01-31",
+            "--older-than",
+            "1d",
+            "--min-size=1k",
+            "--max-size=2M",
+            "--owner=0",
+        ]);
+
+        assert_eq!(args.options.min_size, Some(1024));
+        assert_eq!(args.options.max_size, Some(2 * 1024 * 1024));
+        let now = SystemTime::now();
+        let predicates = args.options.predicates(now).unwrap();
+        assert_eq!(
+            predicates.older_than,
+            now.checked_sub(Duration::from_secs(24 * 60 * 60))
+        );
+        assert!(predicates.newer_than.is_some());
+        assert_eq!(predicates.owner, Some(0));
+
+        TestParser::try_parse_from(["rens-cli", "--min-size=1x"]).unwrap_err();
+        TestParser::try_parse_from(["rens-cli", "--owner=nobody-at-all"])
+            .unwrap_err();
+        TestParser::try_parse_from(["rens-cli", "--newer-than=soon"])
+            .unwrap_err();
+    }
+}
@@ -0,0 +1,176 @@
+//! File names as raw bytes, so names that aren't valid UTF-8 (e.g. legacy
+//! Latin-1 ones) can be matched and renamed too.
+//!
+//! Note: Outside of unix, names are stored as UTF-16 and converted lossily.
+
+/* Built-in imports */
+use std::{
+    ffi::{OsStr, OsString},
+    path::{Path, PathBuf},
+};
+/* Dependencies */
+use bstr::{ByteSlice, ByteVec as _};
+use serde::{Deserialize, Deserializer, Serializer};
+
+/// Bytes of `os_str`, without any conversion on unix.
+#[inline]
+#[must_use]
+pub fn from_os_str(os_str: &OsStr) -> Vec<u8> {
+    Vec::from_os_str_lossy(os_str).into_owned()
+}
+
+/// Reverts [`from_os_str`].
+#[inline]
+#[must_use]
+pub fn into_os_string(bytes: Vec<u8>) -> OsString {
+    bytes.into_os_string_lossy()
+}
+
+/// Displayable form of `bytes`, invalid UTF-8 being escaped as `\xNN`.
+///
+/// Backslashes are doubled on unix, so two names never look the same.
+#[inline]
+#[must_use]
+pub fn escape(bytes: &[u8]) -> String {
+    let mut escaped = String::with_capacity(bytes.len());
+    for chunk in ByteSlice::utf8_chunks(bytes) {
+        if cfg!(unix) {
+            escaped.push_str(&chunk.valid().replace('\\', "\\\\"));
+        } else {
+            escaped.push_str(chunk.valid());
+        }
+        for &byte in chunk.invalid() {
+            push_hex(&mut escaped, byte);
+        }
+    }
+    escaped
+}
+
+/// Reverts [`escape`], `None` if `text` isn't an escaped form
+/// (e.g. a lone backslash on unix).
+#[inline]
+#[must_use]
+pub fn unescape(text: &str) -> Option<Vec<u8>> {
+    if !cfg!(unix) {
+        return Some(text.as_bytes().to_vec());
+    }
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            bytes.extend_from_slice(ch.encode_utf8(&mut [0; 4]).as_bytes());
+            continue;
+        }
+        match chars.next()? {
+            '\\' => bytes.push(b'\\'),
+            'x' => {
+                let high = chars.next()?.to_digit(16)?;
+                let low = chars.next()?.to_digit(16)?;
+                bytes.push(u8::try_from(high * 16 + low).ok()?);
+            },
+            _ => return None,
+        }
+    }
+    Some(bytes)
+}
+
+/// Displayable form of `path`, see [`escape`].
+#[inline]
+#[must_use]
+pub fn escape_path(path: &Path) -> String {
+    escape(&from_os_str(path.as_os_str()))
+}
+
+/// Pushes `byte` as `\xNN`.
+pub(crate) fn push_hex(output: &mut String, byte: u8) {
+    output.push_str("\\x");
+    output.extend(
+        [byte >> 4_u8, byte & 0xF]
+            .into_iter()
+            .filter_map(|nibble| char::from_digit(u32::from(nibble), 16))
+            .map(|digit| digit.to_ascii_uppercase()),
+    );
+}
+
+/// Serde helpers writing paths as strings, or as arrays of bytes when they
+/// aren't valid UTF-8. To use with `#[serde(with = "...")]`.
+pub mod serde_path {
+    use super::{
+        from_os_str, into_os_string, Deserialize, Deserializer, Path, PathBuf,
+        Serializer,
+    };
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Repr {
+        Text(PathBuf),
+        Bytes(Vec<u8>),
+    }
+
+    #[inline]
+    pub fn serialize<P, S>(path: &P, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        P: AsRef<Path>,
+        S: Serializer,
+    {
+        let os_str = path.as_ref().as_os_str();
+        match os_str.to_str() {
+            Some(text) => serializer.serialize_str(text),
+            None => serializer.collect_seq(from_os_str(os_str).iter()),
+        }
+    }
+
+    #[inline]
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<PathBuf, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(match Repr::deserialize(deserializer)? {
+            Repr::Text(path) => path,
+            Repr::Bytes(bytes) => PathBuf::from(into_os_string(bytes)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape(b"plain.txt"), "plain.txt");
+        assert_eq!(escape(b"caf\xe9.txt"), "caf\\xE9.txt");
+        assert_eq!(escape("caf\u{e9}".as_bytes()), "caf\u{e9}");
+        #[cfg(unix)]
+        assert_eq!(escape(b"caf\\xE9"), "caf\\\\xE9");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_unescape() {
+        for name in [&b"plain.txt"[..], b"caf\xe9.txt", b"a\\xE9\\", b"\xff"] {
+            assert_eq!(unescape(&escape(name)).as_deref(), Some(name));
+        }
+        assert_eq!(unescape("a\\b"), None);
+        assert_eq!(unescape("a\\xG0"), None);
+        assert_eq!(unescape("a\\x0"), None);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_serde_path() {
+        #[derive(Debug, PartialEq, Eq, serde::Serialize, Deserialize)]
+        struct Entry {
+            #[serde(with = "serde_path")]
+            path: PathBuf,
+        }
+
+        for name in [&b"caf\xc3\xa9"[..], b"caf\xe9"] {
+            let entry = Entry {
+                path: PathBuf::from(into_os_string(name.to_vec())),
+            };
+            let json = serde_json::to_string(&entry).unwrap();
+            assert_eq!(serde_json::from_str::<Entry>(&json).unwrap(), entry);
+        }
+    }
+}
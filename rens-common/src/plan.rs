@@ -0,0 +1,840 @@
+/* Built-in imports */
+use core::{cmp::Reverse, fmt, ptr};
+use std::{
+    collections::{HashMap, HashSet},
+    fs, io,
+    path::{Path, PathBuf},
+    process,
+};
+/* Dependencies */
+use derive_more::{Constructor, Display};
+use serde::Serialize;
+
+use crate::{
+    file::{self, Name, RenameTarget},
+    os_bytes, File,
+};
+
+/// A single `file -> to` operation of a [`Plan`].
+#[derive(Debug, PartialEq, Eq)]
+pub struct Rename {
+    file: File,
+    to: PathBuf,
+    /// Whether the missing directories leading to `to` get created.
+    create_dirs: bool,
+}
+
+impl Rename {
+    #[inline]
+    #[must_use]
+    pub const fn new(file: File, to: PathBuf) -> Self {
+        Self {
+            file,
+            to,
+            create_dirs: false,
+        }
+    }
+
+    /// Creates the missing directories leading to the destination,
+    /// to move files between directories.
+    #[inline]
+    #[must_use]
+    pub fn creating_dirs(self, create_dirs: bool) -> Self {
+        Self {
+            create_dirs,
+            ..self
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn file(&self) -> &File {
+        &self.file
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn from(&self) -> PathBuf {
+        self.file.path()
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn to(&self) -> &PathBuf {
+        &self.to
+    }
+}
+
+impl fmt::Display for Rename {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} -> {}",
+            os_bytes::escape_path(&self.from()),
+            os_bytes::escape_path(&self.to)
+        )
+    }
+}
+
+/// A batch of [`Rename`]s executed as a whole.
+///
+/// Renames whose destination is the source of another rename of the batch
+/// are reordered so the destination gets freed first (`a -> b, b -> c`),
+/// and cycles (`a -> b, b -> a`) go through a temporary name.
+/// Every chain is executed atomically: if one of its renames fails,
+/// the already executed ones are rolled back.
+///
+/// Note: Directories created for a rename (see [`Rename::creating_dirs`])
+/// are kept on rollback.
+#[derive(Debug, Default, Constructor)]
+pub struct Plan {
+    renames: Vec<Rename>,
+}
+
+/// A single filesystem operation, done on behalf of `rename`.
+#[derive(Debug)]
+struct Step<'plan> {
+    from: PathBuf,
+    to: PathBuf,
+    rename: &'plan Rename,
+}
+
+impl Step<'_> {
+    /// Whether this step moves the file to its final destination.
+    fn completes(&self) -> bool {
+        self.to == self.rename.to
+    }
+}
+
+impl Plan {
+    #[inline]
+    #[must_use]
+    pub fn renames(&self) -> &[Rename] {
+        &self.renames
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.renames.len()
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.renames.is_empty()
+    }
+
+    /// Drops every rename whose destination already exists on disk
+    /// (and won't be freed by another rename of the plan)
+    /// unless `can_override` allows it.
+    ///
+    /// Dropping a rename can make its source an occupied destination
+    /// for another one, so this runs until nothing changes.
+    #[inline]
+    #[must_use]
+    pub fn filter_overrides<F>(mut self, mut can_override: F) -> Self
+    where
+        F: FnMut(&Rename) -> bool,
+    {
+        let mut allowed = HashSet::new();
+        loop {
+            let sources = self.sources();
+            let denied = self.renames.iter().position(|rename| {
+                if !overrides(rename, &sources)
+                    || allowed.contains(&rename.from())
+                {
+                    return false;
+                }
+                let can = can_override(rename);
+                if can {
+                    allowed.insert(rename.from());
+                }
+                !can
+            });
+
+            match denied {
+                Some(idx) => drop(self.renames.remove(idx)),
+                None => return self,
+            }
+        }
+    }
+
+    /// Every rename along with what would prevent it from going smoothly.
+    #[inline]
+    pub fn with_conflicts(
+        &self,
+    ) -> impl Iterator<Item = (&Rename, Option<Conflict>)> {
+        let sources = self.sources();
+        let colliding = self
+            .collisions()
+            .into_iter()
+            .map(|(to, _)| to)
+            .collect::<HashSet<_>>();
+        self.renames.iter().map(move |rename| {
+            let conflict = if colliding.contains(&rename.to) {
+                Some(Conflict::Collision)
+            } else {
+                overrides(rename, &sources).then_some(Conflict::Exists)
+            };
+            (rename, conflict)
+        })
+    }
+
+    /// Renames grouped by destination, for destinations shared by
+    /// more than one rename, in order of first appearance.
+    #[inline]
+    #[must_use]
+    pub fn collisions(&self) -> Vec<(&PathBuf, Vec<&Rename>)> {
+        let mut groups = HashMap::<&PathBuf, Vec<&Rename>>::new();
+        let mut order = Vec::new();
+        for rename in &self.renames {
+            groups
+                .entry(&rename.to)
+                .or_insert_with(|| {
+                    order.push(&rename.to);
+                    Vec::new()
+                })
+                .push(rename);
+        }
+        order
+            .into_iter()
+            .filter_map(|to| groups.remove_entry(to))
+            .filter(|group| group.1.len() > 1)
+            .collect()
+    }
+
+    /// Drops every rename sharing its destination with another one.
+    #[inline]
+    #[must_use]
+    pub fn skip_collisions(mut self) -> Self {
+        let colliding = self
+            .collisions()
+            .into_iter()
+            .map(|(to, _)| to.clone())
+            .collect::<HashSet<_>>();
+        self.renames
+            .retain(|rename| !colliding.contains(&rename.to));
+        self
+    }
+
+    /// Keeps the first rename of each destination as is, and appends
+    /// ` (1)`, ` (2)`... to the stem of the following ones, before their
+    /// `compound` extension.
+    ///
+    /// Suffixed destinations never exist on disk nor in the plan.
+    #[inline]
+    #[must_use]
+    pub fn suffix_collisions(mut self, compound: &[String]) -> Self {
+        let mut taken = self
+            .renames
+            .iter()
+            .flat_map(|rename| [rename.from(), rename.to.clone()])
+            .collect::<HashSet<_>>();
+        let mut seen = HashSet::new();
+        for rename in &mut self.renames {
+            if seen.insert(rename.to.clone()) {
+                continue;
+            }
+            let mut count = 0;
+            rename.to = loop {
+                count += 1;
+                let candidate = suffixed(&rename.to, count, compound);
+                if !candidate.exists() && !taken.contains(&candidate) {
+                    break candidate;
+                }
+            };
+            taken.insert(rename.to.clone());
+        }
+        self
+    }
+
+    fn sources(&self) -> HashSet<PathBuf> {
+        self.renames.iter().map(Rename::from).collect()
+    }
+
+    /// Executes the plan, yielding the outcome of every rename.
+    ///
+    /// Chains are executed one by one as the iterator gets consumed,
+    /// so renamed paths are still valid when yielded.
+    ///
+    /// Note: rollback failures get reported as additional errors.
+    #[inline]
+    pub fn execute(&self) -> impl Iterator<Item = Result<&Rename, Error>> {
+        self.chains()
+            .into_iter()
+            .flat_map(|steps| execute_chain(&steps))
+    }
+
+    /// Splits the plan into chains of steps, in execution order.
+    fn chains(&self) -> Vec<Vec<Step<'_>>> {
+        let sources = self
+            .renames
+            .iter()
+            .map(|rename| (rename.from(), rename))
+            .collect::<HashMap<_, _>>();
+
+        let mut visited = HashSet::new();
+        let mut temporaries = 0;
+        let mut chains = Vec::new();
+        for start in &self.renames {
+            // follow destinations until reaching a free one,
+            // a previous chain or a rename of the current chain (cycle)
+            let mut chain: Vec<&Rename> = Vec::new();
+            let mut cursor = Some(start);
+            let mut cycle_start = None;
+            while let Some(rename) = cursor {
+                if !visited.insert(rename.from()) {
+                    cycle_start =
+                        chain.iter().position(|&cur| ptr::eq(cur, rename));
+                    break;
+                }
+                chain.push(rename);
+                cursor = sources.get(&rename.to).copied();
+            }
+
+            let mut steps = Vec::with_capacity(chain.len() + 1);
+            let (tail, cycle) =
+                chain.split_at(cycle_start.unwrap_or(chain.len()));
+            if let Some((&head, rest)) = cycle.split_first() {
+                let temporary =
+                    temporary_path(head.file.parent(), &mut temporaries);
+                steps.push(Step {
+                    from: head.from(),
+                    to: temporary.clone(),
+                    rename: head,
+                });
+                steps.extend(
+                    rest.iter().rev().map(|&rename| Step::from(rename)),
+                );
+                steps.push(Step {
+                    from: temporary,
+                    to: head.to.clone(),
+                    rename: head,
+                });
+            }
+            steps.extend(tail.iter().rev().map(|&rename| Step::from(rename)));
+
+            if !steps.is_empty() {
+                chains.push(steps);
+            }
+        }
+
+        ordered(chains)
+    }
+}
+
+/// Sorts `chains` so that a chain freeing a path runs before the ones
+/// moving something onto it, and the content of a directory is renamed,
+/// or moved into it, before the directory itself.
+///
+/// Independent chains run deepest first, then in their original order.
+fn ordered(chains: Vec<Vec<Step<'_>>>) -> Vec<Vec<Step<'_>>> {
+    let owners = chains
+        .iter()
+        .enumerate()
+        .flat_map(|(idx, steps)| {
+            steps.iter().map(move |step| (step.from.as_path(), idx))
+        })
+        .collect::<HashMap<_, _>>();
+
+    // `(first, then)` pairs of chain indexes
+    let mut edges = HashSet::new();
+    for (idx, steps) in chains.iter().enumerate() {
+        for step in steps {
+            // The destination must be freed first
+            if let Some(&owner) = owners.get(step.to.as_path()) {
+                edges.insert((owner, idx));
+            }
+            // The content of a directory is renamed before it, so are
+            // the files moved into it
+            let dirs = step.from.ancestors().skip(1);
+            for dir in dirs.chain(step.to.ancestors().skip(1)) {
+                if let Some(&owner) = owners.get(dir) {
+                    edges.insert((idx, owner));
+                }
+            }
+        }
+    }
+
+    // `waiting[idx]` lists the chains to run after `idx`
+    let mut waiting = vec![Vec::new(); chains.len()];
+    let mut pending = vec![0_usize; chains.len()];
+    // Cycles move files onto their own chain's sources
+    for (first, then) in
+        edges.into_iter().filter(|&(first, then)| first != then)
+    {
+        if let Some(next) = waiting.get_mut(first) {
+            next.push(then);
+        }
+        if let Some(count) = pending.get_mut(then) {
+            *count += 1;
+        }
+    }
+
+    let depth = |steps: &[Step<'_>]| {
+        steps
+            .iter()
+            .map(|step| step.from.components().count())
+            .max()
+            .unwrap_or_default()
+    };
+    let mut ready = chains
+        .iter()
+        .enumerate()
+        .filter(|&(idx, _)| pending.get(idx) == Some(&0))
+        .map(|(idx, steps)| (depth(steps), Reverse(idx)))
+        .collect::<Vec<_>>();
+    let mut order = Vec::with_capacity(chains.len());
+    let mut done = vec![false; chains.len()];
+    while order.len() < chains.len() {
+        // Conflicting constraints can't all be met, the earliest
+        // remaining chain goes first then
+        let deepest = ready
+            .iter()
+            .enumerate()
+            .max_by_key(|&(_, key)| key)
+            .map(|(pos, _)| pos);
+        let Some((_, Reverse(idx))) =
+            deepest.map(|pos| ready.swap_remove(pos)).or_else(|| {
+                let idx = done.iter().position(|&finished| !finished)?;
+                Some((0, Reverse(idx)))
+            })
+        else {
+            break;
+        };
+        if done.get(idx) != Some(&false) {
+            continue;
+        }
+        if let Some(finished) = done.get_mut(idx) {
+            *finished = true;
+        }
+        order.push(idx);
+        for &next in waiting.get(idx).into_iter().flatten() {
+            if let Some(count) = pending.get_mut(next) {
+                *count = count.saturating_sub(1);
+                if *count == 0 {
+                    if let Some(steps) = chains.get(next) {
+                        ready.push((depth(steps), Reverse(next)));
+                    }
+                }
+            }
+        }
+    }
+
+    let mut slots = chains.into_iter().map(Some).collect::<Vec<_>>();
+    order
+        .into_iter()
+        .filter_map(|idx| slots.get_mut(idx).and_then(Option::take))
+        .collect()
+}
+
+impl<'plan> From<&'plan Rename> for Step<'plan> {
+    fn from(rename: &'plan Rename) -> Self {
+        Self {
+            from: rename.from(),
+            to: rename.to.clone(),
+            rename,
+        }
+    }
+}
+
+/// Executes every step of the chain, rolling back on failure.
+fn execute_chain<'plan>(
+    steps: &[Step<'plan>],
+) -> Vec<Result<&'plan Rename, Error>> {
+    let mut done = 0;
+    let mut failure = None;
+    for step in steps {
+        if let Err(err) =
+            file::prepare_move(&step.from, &step.to, step.rename.create_dirs)
+                .and_then(|()| fs::rename(&step.from, &step.to))
+        {
+            failure = Some((step.rename, err));
+            break;
+        }
+        done += 1;
+    }
+
+    let renames = steps.iter().filter(|step| step.completes());
+    let Some((failed, mut failure_source)) =
+        failure.map(|(rename, err)| (rename, Some(err)))
+    else {
+        return renames.map(|step| Ok(step.rename)).collect();
+    };
+
+    let rollback_errors = steps.iter().take(done).rev().filter_map(|step| {
+        fs::rename(&step.to, &step.from)
+            .err()
+            .map(|source| Error::Rollback {
+                from: step.to.clone(),
+                to: step.from.clone(),
+                source,
+            })
+    });
+
+    renames
+        .map(|step| {
+            let (from, to) = (step.rename.from(), step.rename.to.clone());
+            let cause = if ptr::eq(step.rename, failed) {
+                failure_source.take()
+            } else {
+                None
+            };
+            Err(match cause {
+                Some(source) => Error::Rename { from, to, source },
+                None => Error::Canceled { from, to },
+            })
+        })
+        .chain(rollback_errors.map(Err))
+        .collect()
+}
+
+impl IntoIterator for Plan {
+    type IntoIter = <Vec<Rename> as IntoIterator>::IntoIter;
+    type Item = Rename;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.renames.into_iter()
+    }
+}
+
+impl FromIterator<Rename> for Plan {
+    #[inline]
+    fn from_iter<T: IntoIterator<Item = Rename>>(iter: T) -> Self {
+        Self::new(iter.into_iter().collect())
+    }
+}
+
+/// Whether `rename` would override a file which isn't renamed by the plan.
+fn overrides(rename: &Rename, sources: &HashSet<PathBuf>) -> bool {
+    rename.to.exists() && !sources.contains(&rename.to)
+}
+
+/// Appends ` (count)` to the stem of `path`, `compound` extensions
+/// being kept whole.
+fn suffixed(path: &Path, count: usize, compound: &[String]) -> PathBuf {
+    let name = Name::from_bytes(
+        path.file_name()
+            .map(os_bytes::from_os_str)
+            .unwrap_or_default(),
+    )
+    .to_renamed(RenameTarget::Stem, compound, |stem| {
+        [stem, format!(" ({count})").as_bytes()].concat()
+    });
+    path.with_file_name(name.to_os_string())
+}
+
+/// Finds an unused name in `dir`, `counter` ensures two temporaries
+/// of the same plan never share a name.
+fn temporary_path(dir: &Path, counter: &mut usize) -> PathBuf {
+    loop {
+        let path = dir.join(format!(".rens-{}-{counter}.tmp", process::id()));
+        *counter += 1;
+        if !path.exists() {
+            return path;
+        }
+    }
+}
+
+/// Why a [`Rename`] can't be executed as is.
+#[derive(Debug, Display, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Conflict {
+    /// The destination exists and isn't renamed by the plan.
+    #[display("exists")]
+    Exists,
+    /// Another rename of the plan has the same destination.
+    #[display("collision")]
+    Collision,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("Failed to rename {} -> {}: {source}", os_bytes::escape_path(.from), os_bytes::escape_path(.to))]
+    Rename {
+        from: PathBuf,
+        to: PathBuf,
+        source: io::Error,
+    },
+    #[error("Canceled {} -> {}: another rename of the chain failed.", os_bytes::escape_path(.from), os_bytes::escape_path(.to))]
+    Canceled { from: PathBuf, to: PathBuf },
+    #[error("Failed to roll back {} -> {}: {source}", os_bytes::escape_path(.from), os_bytes::escape_path(.to))]
+    Rollback {
+        from: PathBuf,
+        to: PathBuf,
+        source: io::Error,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rename(dir: &Path, from: &str, to: &str) -> Rename {
+        Rename::new(File::from_path(dir.join(from)).unwrap(), dir.join(to))
+    }
+
+    fn setup(files: &[&str]) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for file in files {
+            fs::write(dir.path().join(file), file).unwrap();
+        }
+        dir
+    }
+
+    fn content(dir: &Path, file: &str) -> String {
+        fs::read_to_string(dir.join(file)).unwrap()
+    }
+
+    #[test]
+    fn test_chain() {
+        let dir = setup(&["a", "b"]);
+        let plan = Plan::from_iter([
+            rename(dir.path(), "a", "b"),
+            rename(dir.path(), "b", "c"),
+        ]);
+
+        assert!(plan.execute().all(|res| res.is_ok()));
+        assert!(!dir.path().join("a").exists());
+        assert_eq!(content(dir.path(), "b"), "a");
+        assert_eq!(content(dir.path(), "c"), "b");
+    }
+
+    #[test]
+    fn test_swap() {
+        let dir = setup(&["a", "b"]);
+        let plan = Plan::from_iter([
+            rename(dir.path(), "a", "b"),
+            rename(dir.path(), "b", "a"),
+        ]);
+
+        assert_eq!(plan.execute().flatten().count(), 2);
+        assert_eq!(content(dir.path(), "a"), "b");
+        assert_eq!(content(dir.path(), "b"), "a");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn test_rotation() {
+        let dir = setup(&["a", "b", "c", "d"]);
+        let plan = Plan::from_iter([
+            rename(dir.path(), "a", "b"),
+            rename(dir.path(), "b", "c"),
+            rename(dir.path(), "c", "a"),
+            rename(dir.path(), "d", "e"),
+        ]);
+
+        assert!(plan.execute().all(|res| res.is_ok()));
+        assert_eq!(content(dir.path(), "a"), "c");
+        assert_eq!(content(dir.path(), "b"), "a");
+        assert_eq!(content(dir.path(), "c"), "b");
+        assert_eq!(content(dir.path(), "e"), "d");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 4);
+    }
+
+    #[test]
+    fn test_failed_chain_is_rolled_back() {
+        let dir = setup(&["a", "b"]);
+        let plan = Plan::from_iter([
+            rename(dir.path(), "a", "b"),
+            rename(dir.path(), "b", "missing/b"),
+        ]);
+
+        let results = plan.execute().collect::<Vec<_>>();
+        assert!(matches!(
+            results.as_slice(),
+            [Err(Error::Rename { .. }), Err(Error::Canceled { .. })]
+        ));
+        assert_eq!(content(dir.path(), "a"), "a");
+        assert_eq!(content(dir.path(), "b"), "b");
+    }
+
+    #[test]
+    fn test_moves() {
+        let dir = setup(&["a", "b"]);
+        fs::create_dir_all(dir.path().join("d")).unwrap();
+        let plan = Plan::from_iter(
+            [
+                rename(dir.path(), "a", "x/y/a"),
+                rename(dir.path(), "b", "x/b"),
+                rename(dir.path(), "d", "d/e"),
+            ]
+            .map(|planned| planned.creating_dirs(true)),
+        );
+
+        let results = plan.execute().collect::<Vec<_>>();
+        assert_eq!(results.iter().filter(|res| res.is_ok()).count(), 2);
+        assert!(results.iter().any(|res| matches!(res,
+            Err(Error::Rename { source, .. })
+                if source.kind() == io::ErrorKind::InvalidInput)));
+        assert_eq!(content(dir.path(), "x/y/a"), "a");
+        assert_eq!(content(dir.path(), "x/b"), "b");
+        assert!(dir.path().join("d").is_dir());
+    }
+
+    #[test]
+    fn test_dependent_chains_across_depths() {
+        let dir = setup(&["c"]);
+        fs::create_dir_all(dir.path().join("sub")).unwrap();
+        fs::write(dir.path().join("c"), "orig-c").unwrap();
+        fs::write(dir.path().join("sub/a"), "orig-a").unwrap();
+        let plan = Plan::from_iter([
+            rename(dir.path(), "c", "d"),
+            rename(dir.path(), "sub/a", "c"),
+        ]);
+
+        assert!(plan.execute().all(|res| res.is_ok()));
+        assert_eq!(content(dir.path(), "d"), "orig-c");
+        assert_eq!(content(dir.path(), "c"), "orig-a");
+        assert!(!dir.path().join("sub/a").exists());
+    }
+
+    #[test]
+    fn test_directories_last() {
+        let dir = setup(&[]);
+        fs::create_dir_all(dir.path().join("a/b")).unwrap();
+        fs::write(dir.path().join("a/b/c"), "c").unwrap();
+        let plan = Plan::from_iter([
+            rename(dir.path(), "a", "x"),
+            rename(dir.path(), "a/b", "a/y"),
+            rename(dir.path(), "a/b/c", "a/b/z"),
+        ]);
+
+        assert!(plan.execute().all(|res| res.is_ok()));
+        assert_eq!(content(dir.path(), "x/y/z"), "c");
+
+        // Moved into the directory before it gets renamed
+        fs::create_dir_all(dir.path().join("b")).unwrap();
+        fs::write(dir.path().join("w"), "w").unwrap();
+        let moved = Plan::from_iter([
+            rename(dir.path(), "b", "c"),
+            rename(dir.path(), "w", "b/w"),
+        ]);
+
+        assert!(moved.execute().all(|res| res.is_ok()));
+        assert_eq!(content(dir.path(), "c/w"), "w");
+    }
+
+    #[test]
+    fn test_conflicts() {
+        let dir = setup(&["a", "b", "c", "d"]);
+        let plan = Plan::from_iter([
+            rename(dir.path(), "a", "b"),
+            rename(dir.path(), "c", "d"),
+            rename(dir.path(), "d", "a"),
+        ]);
+
+        let conflicts = plan
+            .with_conflicts()
+            .map(|(_, conflict)| conflict)
+            .collect::<Vec<_>>();
+        assert_eq!(conflicts, [Some(Conflict::Exists), None, None]);
+    }
+
+    #[test]
+    fn test_collisions() {
+        let dir = setup(&["a", "b", "c", "d"]);
+        let plan = Plan::from_iter([
+            rename(dir.path(), "a", "x.txt"),
+            rename(dir.path(), "b", "y"),
+            rename(dir.path(), "c", "x.txt"),
+            rename(dir.path(), "d", "x.txt"),
+        ]);
+
+        let collisions = plan.collisions();
+        assert_eq!(collisions.len(), 1);
+        assert!(matches!(
+            collisions.as_slice(),
+            [(to, group)] if to.ends_with("x.txt") && group.len() == 3
+        ));
+        assert_eq!(
+            plan.with_conflicts()
+                .map(|(_, conflict)| conflict)
+                .collect::<Vec<_>>(),
+            [
+                Some(Conflict::Collision),
+                None,
+                Some(Conflict::Collision),
+                Some(Conflict::Collision)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_skip_collisions() {
+        let dir = setup(&["a", "b", "c"]);
+        let plan = Plan::from_iter([
+            rename(dir.path(), "a", "x"),
+            rename(dir.path(), "b", "y"),
+            rename(dir.path(), "c", "x"),
+        ])
+        .skip_collisions();
+
+        assert_eq!(plan.len(), 1);
+        assert!(plan.collisions().is_empty());
+    }
+
+    #[test]
+    fn test_suffix_collisions() {
+        let dir = setup(&["a", "b", "c", "x (1).txt"]);
+        let plan = Plan::from_iter([
+            rename(dir.path(), "a", "x.txt"),
+            rename(dir.path(), "b", "x.txt"),
+            rename(dir.path(), "c", "x.txt"),
+        ])
+        .suffix_collisions(&[]);
+
+        let destinations =
+            plan.renames().iter().map(Rename::to).collect::<Vec<_>>();
+        assert_eq!(
+            destinations,
+            [
+                &dir.path().join("x.txt"),
+                &dir.path().join("x (2).txt"),
+                &dir.path().join("x (3).txt"),
+            ]
+        );
+        assert!(plan.execute().all(|res| res.is_ok()));
+        assert_eq!(content(dir.path(), "x (1).txt"), "x (1).txt");
+
+        // Compound extensions are kept whole
+        let whole = Plan::from_iter([
+            rename(dir.path(), "x.txt", "x.tar.gz"),
+            rename(dir.path(), "x (2).txt", "x.tar.gz"),
+        ])
+        .suffix_collisions(&file::COMPOUND_EXTENSIONS.map(str::to_owned));
+        assert_eq!(
+            whole.renames().get(1).map(Rename::to),
+            Some(&dir.path().join("x (1).tar.gz"))
+        );
+    }
+
+    #[test]
+    fn test_filter_overrides() {
+        let dir = setup(&["a", "b", "c"]);
+        // denying `b -> c` means `a -> b` would now override `b`
+        let mut asked = Vec::new();
+        let denied = Plan::from_iter([
+            rename(dir.path(), "a", "b"),
+            rename(dir.path(), "b", "c"),
+        ])
+        .filter_overrides(|rename| {
+            asked.push(rename.to().clone());
+            false
+        });
+        assert!(denied.is_empty());
+        assert_eq!(asked, [dir.path().join("c"), dir.path().join("b")]);
+
+        let allowed = Plan::from_iter([
+            rename(dir.path(), "a", "b"),
+            rename(dir.path(), "b", "c"),
+        ])
+        .filter_overrides(|_| true);
+        assert_eq!(allowed.len(), 2);
+    }
+}
@@ -0,0 +1,238 @@
+//! `find`-like predicates on the metadata of the files to rename.
+
+/* Built-in imports */
+use core::time::Duration;
+use std::time::SystemTime;
+/* Crate imports */
+use crate::File;
+/* Dependencies */
+use chrono::{Local, NaiveDate, NaiveDateTime, TimeZone as _};
+use derive_more::Constructor;
+
+#[derive(Debug, Default, Clone, Constructor, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Predicates {
+    /// Modified after this time.
+    pub newer_than: Option<SystemTime>,
+    /// Modified before this time.
+    pub older_than: Option<SystemTime>,
+    /// In bytes, inclusive.
+    pub min_size: Option<u64>,
+    /// In bytes, inclusive.
+    pub max_size: Option<u64>,
+    /// Uid of the owner, see [`parse_owner`].
+    pub owner: Option<u32>,
+}
+
+impl Predicates {
+    #[inline]
+    #[must_use]
+    pub fn matches(&self, file: &File) -> bool {
+        let metadata = file.metadata();
+        let modified = metadata.modified();
+        self.newer_than
+            .map_or(true, |time| modified.is_some_and(|mtime| mtime > time))
+            && self
+                .older_than
+                .map_or(true, |time| modified.is_some_and(|mtime| mtime < time))
+            && self.min_size.map_or(true, |size| metadata.size() >= size)
+            && self.max_size.map_or(true, |size| metadata.size() <= size)
+            && self
+                .owner
+                .map_or(true, |owner| metadata.uid() == Some(owner))
+    }
+}
+
+/// Parses a size like `512`, `10k`, `4M` or `2G`, in powers of 1024.
+#[inline]
+pub fn parse_size(input: &str) -> Result<u64, Error> {
+    let digits = input.trim_end_matches(|letter: char| letter.is_alphabetic());
+    let factor: u64 = match input.get(digits.len()..).unwrap_or_default() {
+        "" | "b" | "B" => 1,
+        "k" | "K" => 1 << 10,
+        "m" | "M" => 1 << 20,
+        "g" | "G" => 1 << 30,
+        "t" | "T" => 1 << 40,
+        _ => return Err(Error::InvalidSize(input.to_owned())),
+    };
+    digits
+        .parse::<u64>()
+        .ok()
+        .and_then(|count| count.checked_mul(factor))
+        .ok_or_else(|| Error::InvalidSize(input.to_owned()))
+}
+
+/// Resolves a user name, or parses a uid.
+#[inline]
+pub fn parse_owner(input: &str) -> Result<u32, Error> {
+    #[cfg(unix)]
+    let named = uzers::get_user_by_name(input).map(|user| user.uid());
+    #[cfg(not(unix))]
+    let named = None;
+    named
+        .or_else(|| input.parse().ok())
+        .ok_or_else(|| Error::UnknownOwner(input.to_owned()))
+}
+
+/// Parses a date like `2024-01-31` or `2024-01-31 12:00:00` (local time),
+/// or a duration before `now` like `90m`, `1d12h` or `2w`.
+#[inline]
+pub fn parse_time(input: &str, now: SystemTime) -> Result<SystemTime, Error> {
+    parse_duration(input)
+        .and_then(|duration| now.checked_sub(duration))
+        .or_else(|| parse_date(input))
+        .ok_or_else(|| Error::InvalidTime(input.to_owned()))
+}
+
+fn parse_duration(input: &str) -> Option<Duration> {
+    let mut total = 0_u64;
+    let mut digits = String::new();
+    for letter in input.chars() {
+        if letter.is_ascii_digit() {
+            digits.push(letter);
+            continue;
+        }
+        let unit = match letter {
+            's' => 1,
+            'm' => 60,
+            'h' => 60 * 60,
+            'd' => 24 * 60 * 60,
+            'w' => 7 * 24 * 60 * 60,
+            _ => return None,
+        };
+        total = total
+            .checked_add(digits.parse::<u64>().ok()?.checked_mul(unit)?)?;
+        digits.clear();
+    }
+    (digits.is_empty() && !input.is_empty()).then(|| Duration::from_secs(total))
+}
+
+fn parse_date(input: &str) -> Option<SystemTime> {
+    let naive = ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(input, format).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(input, "%Y-%m-%d")
+                .ok()?
+                .and_hms_opt(0, 0, 0)
+        })?;
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(SystemTime::from)
+}
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("Invalid size `{0}`, expected a number followed by k, M, G or T.")]
+    InvalidSize(String),
+    #[error(
+        "Invalid time `{0}`, expected a date like `2024-01-31` \
+         or a duration like `1d12h` [units: s, m, h, d, w]."
+    )]
+    InvalidTime(String),
+    #[error("Unknown owner `{0}`, expected a user name or uid.")]
+    UnknownOwner(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("512"), Ok(512));
+        assert_eq!(parse_size("10k"), Ok(10 * 1024));
+        assert_eq!(parse_size("4M"), Ok(4 * 1024 * 1024));
+        assert_eq!(parse_size("2G"), Ok(2 * 1024 * 1024 * 1024));
+        for invalid in ["", "k", "1.5M", "10x", "-1"] {
+            assert_eq!(
+                parse_size(invalid),
+                Err(Error::InvalidSize(invalid.to_owned()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_time() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+        assert_eq!(
+            parse_time("1d12h", now),
+            Ok(now - Duration::from_secs(36 * 60 * 60))
+        );
+        assert_eq!(
+            parse_time("2w", now),
+            Ok(now - Duration::from_secs(14 * 24 * 60 * 60))
+        );
+        let date = parse_time("2024-01-31", now).unwrap();
+        assert_eq!(
+            parse_time("2024-01-31 12:00:00", now),
+            Ok(date + Duration::from_secs(12 * 60 * 60))
+        );
+        for invalid in ["", "12", "1y", "2024-13-01"] {
+            assert_eq!(
+                parse_time(invalid, now),
+                Err(Error::InvalidTime(invalid.to_owned()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_matches() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("file"), "0123456789").unwrap();
+        let file = File::from_path(dir.path().join("file")).unwrap();
+        let now = SystemTime::now();
+        let hour = Duration::from_secs(60 * 60);
+
+        assert!(Predicates::default().matches(&file));
+        assert!(Predicates::new(
+            Some(now - hour),
+            Some(now + hour),
+            Some(10),
+            Some(10),
+            file.metadata().uid(),
+        )
+        .matches(&file));
+        assert!(!Predicates::new(Some(now + hour), None, None, None, None)
+            .matches(&file));
+        assert!(!Predicates::new(None, Some(now - hour), None, None, None)
+            .matches(&file));
+        assert!(
+            !Predicates::new(None, None, Some(11), None, None).matches(&file)
+        );
+        assert!(
+            !Predicates::new(None, None, None, Some(9), None).matches(&file)
+        );
+        #[cfg(unix)]
+        {
+            let uid = file.metadata().uid().unwrap();
+            let owned = |owner: &str| {
+                Predicates::new(
+                    None,
+                    None,
+                    None,
+                    None,
+                    Some(parse_owner(owner).unwrap()),
+                )
+                .matches(&file)
+            };
+            assert!(owned(&uid.to_string()));
+            assert!(owned(&file.metadata().owner().unwrap()));
+            assert!(!owned(&(uid + 1).to_string()));
+        }
+    }
+
+    #[test]
+    fn test_parse_owner() {
+        assert_eq!(parse_owner("4242"), Ok(4242));
+        #[cfg(unix)]
+        assert_eq!(parse_owner("root"), Ok(0));
+        assert_eq!(
+            parse_owner("nobody-at-all"),
+            Err(Error::UnknownOwner("nobody-at-all".to_owned()))
+        );
+    }
+}
@@ -0,0 +1,252 @@
+//! Names that Windows, FAT and exFAT accept, e.g. for shares mounted by
+//! Windows clients.
+//!
+//! See: <https://learn.microsoft.com/en-us/windows/win32/fileio/naming-a-file>.
+
+/* Crate imports */
+use crate::file::Name;
+/* Dependencies */
+use bstr::ByteSlice;
+use derive_more::Display;
+
+/// Characters forbidden in names, along with control characters.
+const FORBIDDEN: [char; 9] = ['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+/// Device names, reserved whatever their extension.
+const RESERVED: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6",
+    "COM7", "COM8", "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6",
+    "LPT7", "LPT8", "LPT9",
+];
+/// Longest name of NTFS, FAT and exFAT, in UTF-16 units.
+pub const MAX_LENGTH: usize = 255;
+
+/// Rule a name can break.
+#[derive(Debug, Display, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum Rule {
+    #[display("invalid UTF-8")]
+    Encoding,
+    #[display("forbidden character")]
+    Character,
+    #[display("trailing dot or space")]
+    Trailing,
+    #[display("reserved name")]
+    Reserved,
+    #[display("too long")]
+    Length,
+}
+
+/// How names breaking a [`Rule`] get fixed.
+///
+/// - Forbidden characters and bytes that aren't valid UTF-8 are replaced.
+/// - Trailing dots and spaces are removed.
+/// - Reserved names (`CON`, `NUL.txt`...) get the replacement appended.
+/// - Names that are too long get their stem truncated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Policy {
+    replacement: String,
+    max_length: usize,
+}
+
+impl Default for Policy {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            replacement: "_".to_owned(),
+            max_length: MAX_LENGTH,
+        }
+    }
+}
+
+impl Policy {
+    /// Replaces with `replacement`, possibly empty, and truncates names
+    /// to `max_length` UTF-16 units.
+    #[inline]
+    pub fn new(replacement: &str, max_length: usize) -> Result<Self, Error> {
+        if let Some(ch) = replacement.chars().find(|&ch| is_forbidden(ch)) {
+            return Err(Error::ForbiddenReplacement(ch));
+        }
+        if max_length == 0 {
+            return Err(Error::NoLength);
+        }
+        Ok(Self {
+            replacement: replacement.to_owned(),
+            max_length,
+        })
+    }
+
+    /// `name` following the rules, along with the ones it broke.
+    #[inline]
+    #[must_use]
+    pub fn sanitize(&self, name: &Name) -> (Name, Vec<Rule>) {
+        let mut rules = Vec::new();
+        let mut text = String::new();
+        for chunk in ByteSlice::utf8_chunks(name.to_bytes().as_slice()) {
+            for ch in chunk.valid().chars() {
+                if is_forbidden(ch) {
+                    rules.push(Rule::Character);
+                    text.push_str(&self.replacement);
+                } else {
+                    text.push(ch);
+                }
+            }
+            if !chunk.invalid().is_empty() {
+                rules.push(Rule::Encoding);
+                text.push_str(&self.replacement);
+            }
+        }
+        if self.trim(&mut text) {
+            rules.push(Rule::Trailing);
+        }
+
+        // `CON.txt` and `con .tar.gz` are reserved too
+        let base_len = text.find('.').unwrap_or(text.len());
+        let base = text.get(..base_len).unwrap_or_default().trim_end();
+        if RESERVED
+            .iter()
+            .any(|reserved| reserved.eq_ignore_ascii_case(base))
+        {
+            rules.push(Rule::Reserved);
+            text.insert_str(base_len, self.filler());
+        }
+
+        if text.encode_utf16().count() > self.max_length {
+            rules.push(Rule::Length);
+            text = self.truncated(&text);
+            self.trim(&mut text);
+        }
+
+        rules.sort_unstable();
+        rules.dedup();
+        (Name::from_bytes(text.into_bytes()), rules)
+    }
+
+    /// Removes trailing dots and spaces, whether there were any.
+    fn trim(&self, text: &mut String) -> bool {
+        let trimmed_len = text.trim_end_matches(['.', ' ']).len();
+        if trimmed_len == text.len() {
+            return false;
+        }
+        text.truncate(trimmed_len);
+        if text.is_empty() {
+            text.push_str(self.filler());
+        }
+        true
+    }
+
+    /// `text` truncated to the max length, keeping its extension if possible.
+    fn truncated(&self, text: &str) -> String {
+        let (stem, extension) = match text.rsplit_once('.') {
+            Some((before, after)) if !before.is_empty() => (before, after),
+            _ => (text, ""),
+        };
+        let ext_len = extension.encode_utf16().count() + 1;
+        let (kept, suffix) =
+            if extension.is_empty() || ext_len >= self.max_length {
+                (text, String::new())
+            } else {
+                (stem, format!(".{extension}"))
+            };
+        let budget = self.max_length - suffix.encode_utf16().count();
+        let mut used = 0;
+        let mut truncated = kept
+            .chars()
+            .take_while(|ch| {
+                used += ch.len_utf16();
+                used <= budget
+            })
+            .collect::<String>();
+        truncated.push_str(&suffix);
+        truncated
+    }
+
+    /// Replacement that can't vanish, for reserved or emptied names.
+    fn filler(&self) -> &str {
+        if self.replacement.trim_end_matches(['.', ' ']).is_empty() {
+            "_"
+        } else {
+            &self.replacement
+        }
+    }
+}
+
+fn is_forbidden(ch: char) -> bool {
+    ch.is_ascii_control() || FORBIDDEN.contains(&ch)
+}
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("The replacement can't contain {0:?}.")]
+    ForbiddenReplacement(char),
+    #[error("The max length must be at least 1.")]
+    NoLength,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::os_bytes;
+
+    fn sanitized(policy: &Policy, name: &[u8]) -> (String, Vec<Rule>) {
+        let (fixed, rules) = policy.sanitize(&Name::from_bytes(name.to_vec()));
+        (fixed.to_string(), rules)
+    }
+
+    #[test]
+    fn test_rules() {
+        let policy = Policy::default();
+        for (name, expected, rules) in [
+            (&b"report.txt"[..], "report.txt", vec![]),
+            (b"a<b>:c?.txt", "a_b__c_.txt", vec![Rule::Character]),
+            (b"tab\there", "tab_here", vec![Rule::Character]),
+            (b"caf\xe9.txt", "caf_.txt", vec![Rule::Encoding]),
+            (b"notes. . .", "notes", vec![Rule::Trailing]),
+            (b"...", "_", vec![Rule::Trailing]),
+            (b"con", "con_", vec![Rule::Reserved]),
+            (b"NUL.tar.gz", "NUL_.tar.gz", vec![Rule::Reserved]),
+            (b"LPT1 .txt", "LPT1 _.txt", vec![Rule::Reserved]),
+            (b"CONSOLE.txt", "CONSOLE.txt", vec![]),
+            (b"AUX?.", "AUX_", vec![Rule::Character, Rule::Trailing]),
+        ] {
+            assert_eq!(
+                sanitized(&policy, name),
+                (expected.to_owned(), rules),
+                "{}",
+                os_bytes::escape(name)
+            );
+        }
+    }
+
+    #[test]
+    fn test_length() {
+        let policy = Policy::new("", 8).unwrap();
+        assert_eq!(
+            sanitized(&policy, b"long name.txt"),
+            ("long.txt".to_owned(), vec![Rule::Length])
+        );
+        assert_eq!(
+            sanitized(&policy, b"archive.extension"),
+            ("archive".to_owned(), vec![Rule::Length])
+        );
+        // UTF-16 units, not bytes
+        assert_eq!(
+            sanitized(&policy, "\u{e9}t\u{e9}.txt".as_bytes()),
+            ("\u{e9}t\u{e9}.txt".to_owned(), vec![])
+        );
+        assert_eq!(
+            sanitized(&policy, b"a?b"),
+            ("ab".to_owned(), vec![Rule::Character])
+        );
+        assert_eq!(sanitized(&policy, b"con").0, "con_");
+    }
+
+    #[test]
+    fn test_invalid_policy() {
+        assert_eq!(
+            Policy::new("a/b", 10),
+            Err(Error::ForbiddenReplacement('/'))
+        );
+        assert_eq!(Policy::new("-", 0), Err(Error::NoLength));
+    }
+}
@@ -3,21 +3,26 @@ mod flag;
 /* Built-in imports */
 use core::str::FromStr;
 /* Crate imports */
+use crate::template::{self, Template};
 use flag::Flag;
 /* Dependencies */
-use regex::{Regex, RegexBuilder};
+use derive_more::Display;
+use regex::bytes::{Regex, RegexBuilder};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Display, Clone)]
+#[display("{source}")]
 pub struct SedPattern {
+    /// The pattern as written.
+    source: String,
     pattern: Regex,
-    with: String,
+    with: Template,
     limit: usize,
 }
 
 impl SedPattern {
     #[must_use]
     #[inline]
-    pub fn export(self) -> (Regex, String, usize) {
+    pub fn export(self) -> (Regex, Template, usize) {
         (self.pattern, self.with, self.limit)
     }
 }
@@ -47,11 +52,12 @@ impl FromStr for SedPattern {
         };
 
         let mut rb = RegexBuilder::new(&parse_segment()?);
-        let with = parse_segment()?;
+        let with = parse_segment()?.parse()?;
         let mut limit = 0;
 
-        Flag::list_from_chars(chars)?.iter().for_each(|&flag| {
-            match flag {
+        Flag::list_from_chars(chars)?
+            .iter()
+            .for_each(|&flag| match flag {
                 Flag::Global => limit = 0,
                 Flag::CaseInsensitive => {
                     rb.case_insensitive(true);
@@ -66,10 +72,10 @@ impl FromStr for SedPattern {
                     rb.ignore_whitespace(true);
                 },
                 Flag::Numbered(num) => limit = num,
-            };
-        });
+            });
 
         Ok(Self {
+            source: input.to_owned(),
             pattern: rb.build()?,
             with,
             limit,
@@ -87,20 +93,24 @@ pub enum Error {
     Regex(#[from] regex::Error),
     #[error("{0}")]
     Flag(#[from] flag::Error),
+    #[error("{0}")]
+    Template(#[from] template::Error),
 }
 
 #[cfg(test)]
+// placeholders look like formatting arguments
+#[allow(clippy::literal_string_with_formatting_args)]
 mod tests {
     use super::*;
-    use core::str::FromStr;
 
     #[test]
     fn test_valid_pattern_with_options() {
         let sed_pattern = SedPattern::from_str("/foo/bar/g").unwrap();
 
         assert_eq!(sed_pattern.pattern.as_str(), "foo");
-        assert_eq!(sed_pattern.with, "bar");
+        assert_eq!(sed_pattern.with.to_string(), "bar");
         assert_eq!(sed_pattern.limit, 0);
+        assert_eq!(sed_pattern.to_string(), "/foo/bar/g");
     }
 
     #[test]
@@ -110,11 +120,22 @@ mod tests {
             let sed_pattern = SedPattern::from_str(&pat).unwrap();
 
             assert_eq!(sed_pattern.pattern.as_str(), "foo");
-            assert_eq!(sed_pattern.with, "bar");
+            assert_eq!(sed_pattern.with.to_string(), "bar");
             assert_eq!(sed_pattern.limit, 0);
         }
     }
 
+    #[test]
+    fn test_replacement_with_placeholder() {
+        let sed_pattern = SedPattern::from_str(r"/f(o+)/{n:03}_\U$1/").unwrap();
+        assert_eq!(sed_pattern.with.to_string(), r"{n:03}_\U$1");
+
+        assert!(matches!(
+            SedPattern::from_str("/foo/{x}/"),
+            Err(Error::Template(template::Error::Unknown(_)))
+        ));
+    }
+
     #[test]
     fn test_empty_segments() {
         SedPattern::from_str("/foo//").unwrap();
@@ -128,7 +149,7 @@ mod tests {
         let sed_pattern = SedPattern::from_str("/foo\\/bar/baz/").unwrap();
 
         assert_eq!(sed_pattern.pattern.as_str(), "foo/bar");
-        assert_eq!(sed_pattern.with, "baz");
+        assert_eq!(sed_pattern.with.to_string(), "baz");
         assert_eq!(sed_pattern.limit, 0);
     }
 
@@ -137,7 +158,7 @@ mod tests {
         let sed_pattern = SedPattern::from_str("/foo/bar\\/baz/").unwrap();
 
         assert_eq!(sed_pattern.pattern.as_str(), "foo");
-        assert_eq!(sed_pattern.with, "bar/baz");
+        assert_eq!(sed_pattern.with.to_string(), "bar/baz");
         assert_eq!(sed_pattern.limit, 0);
     }
 
@@ -146,8 +167,8 @@ mod tests {
         let sed_pattern = SedPattern::from_str("/f.o*/bar/g").unwrap();
 
         assert_eq!(sed_pattern.pattern.as_str(), "f.o*",);
-        assert!(sed_pattern.pattern.is_match("foooooooo"));
-        assert_eq!(sed_pattern.with, "bar",);
+        assert!(sed_pattern.pattern.is_match(b"foooooooo"));
+        assert_eq!(sed_pattern.with.to_string(), "bar",);
         assert_eq!(sed_pattern.limit, 0);
     }
 
@@ -0,0 +1,324 @@
+/* Crate imports */
+use super::{without_separators, Template};
+/* Dependencies */
+use bstr::ByteSlice;
+use derive_more::{Display, FromStr};
+use deunicode::deunicode_with_tofu;
+use regex::bytes::Regex;
+use unicode_normalization::UnicodeNormalization as _;
+
+/// Sed-like case conversion escape.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Escape {
+    /// `\U`, uppercase until `\L` or `\E`.
+    Upper,
+    /// `\L`, lowercase until `\U` or `\E`.
+    Lower,
+    /// `\E`, stops `\U` and `\L`.
+    End,
+    /// `\u`, uppercase the next character.
+    UpperNext,
+    /// `\l`, lowercase the next character.
+    LowerNext,
+}
+
+impl Escape {
+    pub const fn from_char(ch: char) -> Option<Self> {
+        match ch {
+            'U' => Some(Self::Upper),
+            'L' => Some(Self::Lower),
+            'E' => Some(Self::End),
+            'u' => Some(Self::UpperNext),
+            'l' => Some(Self::LowerNext),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Case {
+    Upper,
+    Lower,
+}
+
+impl Case {
+    fn push(self, output: &mut Vec<u8>, ch: char) {
+        match self {
+            Self::Upper => {
+                ch.to_uppercase().for_each(|up| push_char(output, up));
+            },
+            Self::Lower => {
+                ch.to_lowercase().for_each(|low| push_char(output, low));
+            },
+        }
+    }
+}
+
+fn push_char(output: &mut Vec<u8>, ch: char) {
+    output.extend_from_slice(ch.encode_utf8(&mut [0; 4]).as_bytes());
+}
+
+/// Output of a replacement, converted according to the escapes met so far.
+#[derive(Debug, Default)]
+pub struct Cased {
+    output: Vec<u8>,
+    mode: Option<Case>,
+    next: Option<Case>,
+}
+
+impl Cased {
+    pub fn apply(&mut self, escape: Escape) {
+        match escape {
+            Escape::Upper => self.mode = Some(Case::Upper),
+            Escape::Lower => self.mode = Some(Case::Lower),
+            Escape::End => self.mode = None,
+            Escape::UpperNext => self.next = Some(Case::Upper),
+            Escape::LowerNext => self.next = Some(Case::Lower),
+        }
+    }
+
+    pub fn push_str(&mut self, text: &str) {
+        for ch in text.chars() {
+            match self.next.take().or(self.mode) {
+                Some(case) => case.push(&mut self.output, ch),
+                None => push_char(&mut self.output, ch),
+            }
+        }
+    }
+
+    /// Pushes `bytes`, bytes that aren't valid UTF-8 being kept as is.
+    pub fn push_bytes(&mut self, bytes: &[u8]) {
+        for chunk in ByteSlice::utf8_chunks(bytes) {
+            self.push_str(chunk.valid());
+            self.output.extend_from_slice(chunk.invalid());
+        }
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.output
+    }
+}
+
+/// Named transform, applied to a capture group with `{$1:snake}`.
+#[derive(Debug, Display, Clone, Copy, FromStr, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Transform {
+    /// `UPPERCASE`.
+    Upper,
+    /// `lowercase`.
+    Lower,
+    /// `snake_case`.
+    Snake,
+    /// `kebab-case`.
+    Kebab,
+    /// `camelCase`.
+    Camel,
+    /// `PascalCase`.
+    Pascal,
+    /// `Title Case`.
+    Title,
+    /// Unicode NFC, accents composed with their letter (`é`).
+    Nfc,
+    /// Unicode NFD, accents decomposed after their letter (`e` + `´`),
+    /// as files synced from macOS often are.
+    Nfd,
+    /// ASCII transliteration (`é` is `e`, `ß` is `ss`, `ж` is `zh`),
+    /// characters without any becoming `_`, as do transliterated separators
+    /// (`½` isn't `1/2` but `1_2`).
+    Ascii,
+}
+
+impl Transform {
+    #[inline]
+    #[must_use]
+    pub fn apply(self, text: &str) -> String {
+        let capitalized = |word: &str| {
+            let mut chars = word.chars();
+            chars.next().map_or_else(String::new, |first| {
+                first
+                    .to_uppercase()
+                    .chain(chars.flat_map(char::to_lowercase))
+                    .collect()
+            })
+        };
+        let words = words(text);
+        match self {
+            Self::Upper => text.to_uppercase(),
+            Self::Lower => text.to_lowercase(),
+            Self::Snake => words
+                .iter()
+                .map(|word| word.to_lowercase())
+                .collect::<Vec<_>>()
+                .join("_"),
+            Self::Kebab => words
+                .iter()
+                .map(|word| word.to_lowercase())
+                .collect::<Vec<_>>()
+                .join("-"),
+            Self::Camel => words
+                .iter()
+                .enumerate()
+                .map(|(idx, word)| {
+                    if idx == 0 {
+                        word.to_lowercase()
+                    } else {
+                        capitalized(word)
+                    }
+                })
+                .collect(),
+            Self::Pascal => {
+                words.iter().map(|word| capitalized(word)).collect()
+            },
+            Self::Title => words
+                .iter()
+                .map(|word| capitalized(word))
+                .collect::<Vec<_>>()
+                .join(" "),
+            Self::Nfc => text.nfc().collect(),
+            Self::Nfd => text.nfd().collect(),
+            Self::Ascii => transliterated(text),
+        }
+    }
+
+    /// Regex, replacement and limit applying the transform to whole names,
+    /// like [`SedPattern::export`](crate::SedPattern::export).
+    ///
+    /// Note: Bytes that aren't valid UTF-8 are left as is.
+    ///
+    /// # Panics
+    ///
+    /// Never, the regex being valid.
+    #[inline]
+    #[must_use]
+    pub fn export(self) -> (Regex, Template, usize) {
+        // `.` only matches valid UTF-8, in unicode mode
+        #[allow(clippy::expect_used)]
+        let pattern = Regex::new("(?s).+").expect("Unable to build regex.");
+        (pattern, Template::from(self), 0)
+    }
+}
+
+/// Splits `text` into words, on anything that isn't alphanumeric
+/// and on case changes (`HTTPServer` is `HTTP` and `Server`).
+fn words(text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut chars = text.chars().peekable();
+    let mut previous: Option<char> = None;
+
+    while let Some(ch) = chars.next() {
+        if !ch.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(current.clone());
+                current.clear();
+            }
+            previous = None;
+            continue;
+        }
+        let boundary = previous.is_some_and(|prev| {
+            ch.is_uppercase()
+                && (prev.is_lowercase()
+                    || prev.is_numeric()
+                    || (prev.is_uppercase()
+                        && chars
+                            .peek()
+                            .is_some_and(|next| next.is_lowercase())))
+        });
+        if boundary && !current.is_empty() {
+            words.push(current.clone());
+            current.clear();
+        }
+        current.push(ch);
+        previous = Some(ch);
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+/// ASCII transliteration of `text`, never adding path separators or NUL.
+fn transliterated(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(|ch: char| !ch.is_ascii()) {
+        let (ascii, others) = rest.split_at(start);
+        output.push_str(ascii);
+        let end = others
+            .find(|ch: char| ch.is_ascii())
+            .unwrap_or(others.len());
+        let (run, next) = others.split_at(end);
+        output.push_str(&without_separators(&deunicode_with_tofu(run, "_")));
+        rest = next;
+    }
+    output.push_str(rest);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_words() {
+        assert_eq!(
+            words("HTTPServer_error-2x"),
+            ["HTTP", "Server", "error", "2x"]
+        );
+        assert_eq!(words("myFile v2"), ["my", "File", "v2"]);
+        assert!(words(" _- ").is_empty());
+    }
+
+    #[test]
+    fn test_transforms() {
+        let text = "my holidayPhotos-2024";
+        for (transform, expected) in [
+            ("upper", "MY HOLIDAYPHOTOS-2024"),
+            ("lower", "my holidayphotos-2024"),
+            ("snake", "my_holiday_photos_2024"),
+            ("kebab", "my-holiday-photos-2024"),
+            ("camel", "myHolidayPhotos2024"),
+            ("pascal", "MyHolidayPhotos2024"),
+            ("title", "My Holiday Photos 2024"),
+        ] {
+            assert_eq!(
+                Transform::from_str(transform).unwrap().apply(text),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_normalization() {
+        let composed = "Cr\u{e8}me br\u{fb}l\u{e9}e";
+        let decomposed = "Cre\u{300}me bru\u{302}le\u{301}e";
+        assert_eq!(Transform::Nfd.apply(composed), decomposed);
+        assert_eq!(Transform::Nfc.apply(decomposed), composed);
+        assert_eq!(Transform::Ascii.apply(decomposed), "Creme brulee");
+        assert_eq!(
+            Transform::Ascii.apply("Stra\u{df}e \u{416}\u{443}\u{43a}"),
+            "Strasse Zhuk"
+        );
+        // Transliterations can't move files
+        assert_eq!(Transform::Ascii.apply("AC\u{2215}DC"), "AC_DC");
+        assert_eq!(Transform::Ascii.apply("\u{bd} cup"), "1_2 cup");
+        assert_eq!(Transform::Ascii.apply("a/b\u{2044}c"), "a/b_c");
+    }
+
+    #[test]
+    fn test_escapes() {
+        let mut cased = Cased::default();
+        cased.push_str("a");
+        cased.apply(Escape::Upper);
+        cased.push_str("bc");
+        cased.apply(Escape::LowerNext);
+        cased.push_str("DE");
+        cased.apply(Escape::End);
+        cased.push_str("f");
+        cased.apply(Escape::UpperNext);
+        cased.apply(Escape::Lower);
+        cased.push_str("gHI");
+        cased.push_bytes(b"\xe9j");
+        assert_eq!(cased.into_bytes(), b"aBCdEfGhi\xe9j");
+    }
+}
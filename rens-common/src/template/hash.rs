@@ -0,0 +1,219 @@
+/* Built-in imports */
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Read as _},
+    path::{Path, PathBuf},
+};
+/* Dependencies */
+use derive_more::{Display, FromStr};
+use rayon::iter::{IntoParallelIterator as _, ParallelIterator as _};
+use sha2::Digest;
+
+use crate::{traits::FileKind, File};
+
+/// Size of the chunks files are hashed by.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+#[derive(
+    Debug, Default, Display, Clone, Copy, FromStr, PartialEq, Eq, Hash,
+)]
+#[non_exhaustive]
+pub enum Algorithm {
+    Md5,
+    Sha1,
+    #[default]
+    Sha256,
+    Sha512,
+    Blake3,
+}
+
+impl Algorithm {
+    /// Hex digest of the content of `path`, read by chunks.
+    #[inline]
+    pub fn digest<P: AsRef<Path>>(self, path: P) -> io::Result<String> {
+        let mut reader = fs::File::open(path)?;
+        match self {
+            Self::Md5 => stream::<md5::Md5>(&mut reader),
+            Self::Sha1 => stream::<sha1::Sha1>(&mut reader),
+            Self::Sha256 => stream::<sha2::Sha256>(&mut reader),
+            Self::Sha512 => stream::<sha2::Sha512>(&mut reader),
+            Self::Blake3 => {
+                let mut hasher = blake3::Hasher::new();
+                hasher.update_reader(reader)?;
+                Ok(hasher.finalize().to_hex().to_string())
+            },
+        }
+    }
+}
+
+fn stream<D: Digest>(reader: &mut fs::File) -> io::Result<String> {
+    let mut hasher = D::new();
+    let mut chunk = vec![0; CHUNK_SIZE];
+    loop {
+        match reader.read(&mut chunk)? {
+            0 => break,
+            read => hasher.update(chunk.get(..read).unwrap_or_default()),
+        }
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .flat_map(|byte| [byte >> 4_u8, byte & 0xF])
+        .filter_map(|nibble| char::from_digit(nibble.into(), 16))
+        .collect())
+}
+
+/// `{hash:sha256:8}` placeholder, the length being optional.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Field {
+    algorithm: Algorithm,
+    length: Option<usize>,
+}
+
+impl Field {
+    #[inline]
+    #[must_use]
+    pub const fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    /// Parses the format of a `{hash:format}` placeholder.
+    pub fn parse(format: &str) -> Result<Self, String> {
+        let invalid = |_err| format.to_owned();
+        let (algorithm, truncation) = match format.split_once(':') {
+            Some((algorithm, length)) => {
+                (algorithm.parse().map_err(invalid)?, length)
+            },
+            None if format.is_empty() => (Algorithm::default(), ""),
+            // `{hash:8}` or `{hash:sha256}`
+            None => format.parse().map_or_else(
+                |_err| (Algorithm::default(), format),
+                |algorithm| (algorithm, ""),
+            ),
+        };
+        let length = (!truncation.is_empty())
+            .then(|| truncation.parse())
+            .transpose()
+            .map_err(|_err| format.to_owned())?;
+        Ok(Self { algorithm, length })
+    }
+
+    /// Digest of `file`, truncated, empty if it can't be read.
+    pub fn render(self, file: &File, digests: &Digests) -> String {
+        let digest = digests.get(file, self.algorithm).map_or_else(
+            || self.algorithm.digest(file.path()).unwrap_or_default(),
+            str::to_owned,
+        );
+        match self.length {
+            Some(length) => digest.chars().take(length).collect(),
+            None => digest,
+        }
+    }
+}
+
+/// Digests computed ahead of renaming, in parallel.
+#[derive(Debug, Default, Clone)]
+pub struct Digests {
+    computed: HashMap<(PathBuf, Algorithm), String>,
+}
+
+impl Digests {
+    /// Hashes the regular files among `files` with every algorithm.
+    ///
+    /// Note: files that can't be read are left out, they are hashed
+    /// (and fail) again when rendering.
+    #[inline]
+    #[must_use]
+    pub fn compute(files: &[File], algorithms: &[Algorithm]) -> Self {
+        let jobs = files
+            .iter()
+            .filter(|file| file.kind() == FileKind::File)
+            .flat_map(|file| {
+                algorithms.iter().map(|&algorithm| (file.path(), algorithm))
+            })
+            .collect::<Vec<_>>();
+        let computed = jobs
+            .into_par_iter()
+            .filter_map(|(path, algorithm)| {
+                let digest = algorithm.digest(&path).ok()?;
+                Some(((path, algorithm), digest))
+            })
+            .collect();
+        Self { computed }
+    }
+
+    /// Adds the digests of `other`, e.g. computed with new algorithms.
+    #[inline]
+    pub fn merge(&mut self, other: Self) {
+        self.computed.extend(other.computed);
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn get(&self, file: &File, algorithm: Algorithm) -> Option<&str> {
+        self.computed
+            .get(&(file.path(), algorithm))
+            .map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::slice;
+
+    #[test]
+    fn test_digests() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file");
+        // spans several chunks
+        fs::write(&path, "rens".repeat(CHUNK_SIZE)).unwrap();
+        let file = File::from_path(&path).unwrap();
+
+        let algorithms = [Algorithm::Sha256, Algorithm::Blake3];
+        let digests = Digests::compute(slice::from_ref(&file), &algorithms);
+        for algorithm in algorithms {
+            assert_eq!(
+                digests.get(&file, algorithm).unwrap(),
+                algorithm.digest(&path).unwrap()
+            );
+        }
+        assert_eq!(digests.get(&file, Algorithm::Md5), None);
+    }
+
+    #[test]
+    fn test_render() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file");
+        fs::write(&path, "abc").unwrap();
+        let file = File::from_path(&path).unwrap();
+        let render = |format: &str| {
+            Field::parse(format)
+                .unwrap()
+                .render(&file, &Digests::default())
+        };
+
+        assert_eq!(render("md5"), "900150983cd24fb0d6963f7d28e17f72");
+        assert_eq!(render("sha1:8"), "a9993e36");
+        assert_eq!(render("sha256:8"), "ba7816bf");
+        assert_eq!(render("8"), "ba7816bf");
+        assert_eq!(render("").len(), 64);
+        assert_eq!(render("sha512:4"), "ddaf");
+        assert_eq!(render("blake3:6"), "6437b3");
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            Field::parse("blake3:12"),
+            Ok(Field {
+                algorithm: Algorithm::Blake3,
+                length: Some(12)
+            })
+        );
+        assert_eq!(Field::parse("crc:8"), Err("crc:8".to_owned()));
+        assert_eq!(Field::parse("sha256:x"), Err("sha256:x".to_owned()));
+        assert_eq!(Field::parse("x"), Err("x".to_owned()));
+    }
+}
@@ -0,0 +1,407 @@
+/* Built-in imports */
+use core::iter;
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, BufReader, Read},
+    path::{Path, PathBuf},
+};
+/* Dependencies */
+use chrono::NaiveDateTime;
+use exif::{In, Reader, Value};
+use id3::TagLike as _;
+use rayon::iter::{IntoParallelIterator as _, ParallelIterator as _};
+
+use super::{metadata::time_format, Padding};
+use crate::{traits::FileKind, File};
+
+/// How EXIF stores dates.
+const EXIF_DATE_FORMAT: &str = "%Y:%m:%d %H:%M:%S";
+
+/// Placeholder pulling from the tags embedded in the file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Field {
+    /// `{exif:Model}`, dates accepting a format like
+    /// `{exif:DateTimeOriginal:%Y-%m-%d}`.
+    Exif { tag: String, date_format: String },
+    /// `{id3:artist}`, numbers accepting a padding like `{id3:track:02}`.
+    ///
+    /// Note: FLAC files are read through their Vorbis comments.
+    Audio(AudioTag, Padding),
+}
+
+/// Tag embedded in the file, before being formatted.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Tag {
+    Exif(String),
+    Audio(AudioTag),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum AudioTag {
+    Artist,
+    AlbumArtist,
+    Album,
+    Title,
+    Genre,
+    Track,
+    Disc,
+    Year,
+}
+
+impl AudioTag {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "artist" => Some(Self::Artist),
+            "album_artist" | "albumartist" => Some(Self::AlbumArtist),
+            "album" => Some(Self::Album),
+            "title" => Some(Self::Title),
+            "genre" => Some(Self::Genre),
+            "track" => Some(Self::Track),
+            "disc" => Some(Self::Disc),
+            "year" => Some(Self::Year),
+            _ => None,
+        }
+    }
+
+    const fn is_number(self) -> bool {
+        matches!(self, Self::Track | Self::Disc | Self::Year)
+    }
+
+    /// Name of the matching Vorbis comment.
+    const fn vorbis_key(self) -> &'static str {
+        match self {
+            Self::Artist => "ARTIST",
+            Self::AlbumArtist => "ALBUMARTIST",
+            Self::Album => "ALBUM",
+            Self::Title => "TITLE",
+            Self::Genre => "GENRE",
+            Self::Track => "TRACKNUMBER",
+            Self::Disc => "DISCNUMBER",
+            Self::Year => "DATE",
+        }
+    }
+
+    fn read_id3(self, tag: &id3::Tag) -> Option<String> {
+        match self {
+            Self::Artist => tag.artist().map(str::to_owned),
+            Self::AlbumArtist => tag.album_artist().map(str::to_owned),
+            Self::Album => tag.album().map(str::to_owned),
+            Self::Title => tag.title().map(str::to_owned),
+            Self::Genre => tag.genre_parsed().map(|genre| genre.to_string()),
+            Self::Track => tag.track().map(|track| track.to_string()),
+            Self::Disc => tag.disc().map(|disc| disc.to_string()),
+            Self::Year => tag
+                .year()
+                .or_else(|| tag.date_recorded().map(|date| date.year))
+                .map(|year| year.to_string()),
+        }
+    }
+}
+
+impl Field {
+    /// Parses the `{name:format}` placeholder, `None` if `name` isn't
+    /// a media placeholder, `Some(Err(format))` if `format` is invalid.
+    pub fn parse(name: &str, format: &str) -> Option<Result<Self, String>> {
+        let (tag, tag_format) = format.split_once(':').unwrap_or((format, ""));
+        let invalid = || format.to_owned();
+
+        Some(match name {
+            "exif" if !tag.is_empty() => time_format(tag_format)
+                .map(|date_format| Self::Exif {
+                    tag: tag.to_owned(),
+                    date_format,
+                })
+                .map_err(|_err| invalid()),
+            "id3" => match AudioTag::from_name(tag) {
+                Some(audio_tag)
+                    if audio_tag.is_number() || tag_format.is_empty() =>
+                {
+                    tag_format
+                        .parse()
+                        .map(|padding| Self::Audio(audio_tag, padding))
+                        .map_err(|_err| invalid())
+                },
+                _ => Err(invalid()),
+            },
+            "exif" => Err(invalid()),
+            _ => return None,
+        })
+    }
+
+    /// Tag the placeholder is rendered from.
+    pub fn tag(&self) -> Tag {
+        match self.clone() {
+            Self::Exif { tag, .. } => Tag::Exif(tag),
+            Self::Audio(audio_tag, _) => Tag::Audio(audio_tag),
+        }
+    }
+
+    /// Value of the tag, empty if the file doesn't have it.
+    ///
+    /// Note: tags missing from `tags` are read from the file.
+    pub fn render(&self, file: &File, tags: &Tags) -> String {
+        let tag = self.tag();
+        let value = tags
+            .get(file, &tag)
+            .map_or_else(
+                || tag.read(&file.path()),
+                |read| Some(read.to_owned()),
+            )
+            .unwrap_or_default();
+        match self.clone() {
+            Self::Exif { date_format, .. } => {
+                NaiveDateTime::parse_from_str(&value, EXIF_DATE_FORMAT)
+                    .map_or(value, |date| date.format(&date_format).to_string())
+            },
+            Self::Audio(audio_tag, padding) => {
+                if !audio_tag.is_number() {
+                    return value;
+                }
+                // `3/12` tracks, `2024-03-01` dates
+                value
+                    .split(|ch: char| !ch.is_ascii_digit())
+                    .next()
+                    .and_then(|number| number.parse().ok())
+                    .map_or_else(
+                        || value.clone(),
+                        |number| padding.apply(number),
+                    )
+            },
+        }
+    }
+}
+
+impl Tag {
+    /// Value of the tag in the file at `path`, if any.
+    fn read(&self, path: &Path) -> Option<String> {
+        match self.clone() {
+            Self::Exif(tag) => exif_value(path, &tag),
+            Self::Audio(audio_tag) => audio_value(path, audio_tag),
+        }
+    }
+}
+
+/// Tags read ahead of renaming, in parallel.
+#[derive(Debug, Default, Clone)]
+pub struct Tags {
+    /// Missing tags are read as empty values.
+    read: HashMap<(PathBuf, Tag), String>,
+}
+
+impl Tags {
+    /// Reads every tag of the regular files among `files`.
+    #[inline]
+    #[must_use]
+    pub fn read(files: &[File], tags: &[Tag]) -> Self {
+        let jobs = files
+            .iter()
+            .filter(|file| file.kind() == FileKind::File)
+            .flat_map(|file| tags.iter().map(|tag| (file.path(), tag.clone())))
+            .collect::<Vec<_>>();
+        let read = jobs
+            .into_par_iter()
+            .map(|(path, tag)| {
+                let value = tag.read(&path).unwrap_or_default();
+                ((path, tag), value)
+            })
+            .collect();
+        Self { read }
+    }
+
+    /// Adds the tags of `other`, e.g. read for new placeholders.
+    #[inline]
+    pub fn merge(&mut self, other: Self) {
+        self.read.extend(other.read);
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn get(&self, file: &File, tag: &Tag) -> Option<&str> {
+        self.read
+            .get(&(file.path(), tag.clone()))
+            .map(String::as_str)
+    }
+}
+
+fn exif_value(path: &Path, tag: &str) -> Option<String> {
+    let mut reader = BufReader::new(fs::File::open(path).ok()?);
+    let exif = Reader::new().read_from_container(&mut reader).ok()?;
+    let field = exif
+        .fields()
+        .filter(|field| field.tag.to_string() == tag)
+        // prefer the main image over thumbnails
+        .min_by_key(|field| field.ifd_num != In::PRIMARY)?;
+
+    if let Value::Ascii(values) = field.value.clone() {
+        return values
+            .first()
+            .map(|value| String::from_utf8_lossy(value).trim().to_owned());
+    }
+    Some(field.display_value().to_string())
+}
+
+fn audio_value(path: &Path, audio_tag: AudioTag) -> Option<String> {
+    let is_flac = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("flac"));
+    if is_flac {
+        vorbis_comments(path).ok()?.remove(audio_tag.vorbis_key())
+    } else {
+        audio_tag.read_id3(&id3::Tag::read_from_path(path).ok()?)
+    }
+}
+
+/// Reads the Vorbis comments of a FLAC file, keys being uppercased.
+///
+/// See: <https://xiph.org/flac/format.html#metadata_block>.
+#[allow(clippy::big_endian_bytes)]
+fn vorbis_comments(path: &Path) -> io::Result<HashMap<String, String>> {
+    const VORBIS_COMMENT: u8 = 4;
+    let invalid = |msg| io::Error::new(io::ErrorKind::InvalidData, msg);
+
+    let mut reader = BufReader::new(fs::File::open(path)?);
+    let mut magic = [0; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic != b"fLaC" {
+        return Err(invalid("Not a FLAC file."));
+    }
+
+    loop {
+        let mut header = [0; 4];
+        reader.read_exact(&mut header)?;
+        let [flags, len_hi, len_mid, len_lo] = header;
+        let mut block = reader
+            .by_ref()
+            .take(u32::from_be_bytes([0, len_hi, len_mid, len_lo]).into());
+        if flags & 0x7F == VORBIS_COMMENT {
+            let vendor_len = read_u32_le(&mut block)?;
+            io::copy(&mut block.by_ref().take(vendor_len), &mut io::sink())?;
+            let count = usize::try_from(read_u32_le(&mut block)?)
+                .map_err(|_err| invalid("Too many Vorbis comments."))?;
+            return iter::repeat_with(|| {
+                let mut comment = Vec::new();
+                let len = read_u32_le(&mut block)?;
+                block.by_ref().take(len).read_to_end(&mut comment)?;
+                String::from_utf8_lossy(&comment)
+                    .split_once('=')
+                    .map(|(key, value)| (key.to_uppercase(), value.to_owned()))
+                    .ok_or_else(|| invalid("Malformed Vorbis comment."))
+            })
+            .take(count)
+            .collect();
+        }
+        io::copy(&mut block, &mut io::sink())?;
+        if flags & 0x80 != 0 {
+            return Ok(HashMap::new());
+        }
+    }
+}
+
+#[allow(clippy::little_endian_bytes)]
+fn read_u32_le<R: Read>(reader: &mut R) -> io::Result<u64> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes).into())
+}
+
+#[cfg(test)]
+#[allow(clippy::literal_string_with_formatting_args)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> File {
+        File::from_path(
+            Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("fixtures")
+                .join(name),
+        )
+        .unwrap()
+    }
+
+    fn field(placeholder: &str) -> Field {
+        let (name, format) = placeholder.split_once(':').unwrap();
+        Field::parse(name, format).unwrap().unwrap()
+    }
+
+    fn render(placeholder: &str, fixture_name: &str) -> String {
+        field(placeholder).render(&fixture(fixture_name), &Tags::default())
+    }
+
+    #[test]
+    fn test_exif() {
+        for fixture in ["sample.jpg", "sample.tiff"] {
+            assert_eq!(render("exif:Model", fixture), "Rens Camera");
+            assert_eq!(render("exif:DateTimeOriginal", fixture), "2024-03-01");
+            assert_eq!(
+                render("exif:DateTimeOriginal:%Y%m%d_%H%M%S", fixture),
+                "20240301_123456"
+            );
+            assert_eq!(render("exif:Artist", fixture), "");
+        }
+        assert_eq!(render("exif:Model", "sample.mp3"), "");
+    }
+
+    #[test]
+    fn test_id3() {
+        assert_eq!(render("id3:artist", "sample.mp3"), "Rens Band");
+        assert_eq!(render("id3:album", "sample.mp3"), "Rename Hits");
+        assert_eq!(render("id3:track", "sample.mp3"), "3");
+        assert_eq!(render("id3:track:02", "sample.mp3"), "03");
+        assert_eq!(render("id3:year", "sample.mp3"), "2024");
+        assert_eq!(render("id3:genre", "sample.mp3"), "");
+    }
+
+    #[test]
+    fn test_flac() {
+        assert_eq!(render("id3:artist", "sample.flac"), "Rens Band");
+        assert_eq!(render("id3:title", "sample.flac"), "Other Song");
+        assert_eq!(render("id3:track:03", "sample.flac"), "007");
+        assert_eq!(render("id3:year", "sample.flac"), "2023");
+        assert_eq!(render("id3:album", "sample.flac"), "");
+    }
+
+    #[test]
+    fn test_tags() {
+        let files = ["sample.jpg", "sample.mp3"].map(fixture);
+        let placeholders =
+            ["exif:DateTimeOriginal:%Y", "id3:track:02", "id3:genre"];
+        let tags = Tags::read(
+            &files,
+            &placeholders.map(|placeholder| field(placeholder).tag()),
+        );
+
+        for file in &files {
+            for placeholder in placeholders {
+                assert_eq!(
+                    field(placeholder).render(file, &tags),
+                    field(placeholder).render(file, &Tags::default())
+                );
+            }
+        }
+        let [photo, song] = files;
+        assert_eq!(
+            tags.get(&photo, &field("exif:DateTimeOriginal").tag()),
+            Some("2024:03:01 12:34:56")
+        );
+        assert_eq!(tags.get(&song, &field("id3:genre").tag()), Some(""));
+        assert_eq!(tags.get(&song, &field("id3:artist").tag()), None);
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Field::parse("mtime", ""), None);
+        assert_eq!(Field::parse("exif", ""), Some(Err(String::new())));
+        assert_eq!(
+            Field::parse("exif", "DateTime:%Q"),
+            Some(Err("DateTime:%Q".to_owned()))
+        );
+        assert_eq!(
+            Field::parse("id3", "artist:02"),
+            Some(Err("artist:02".to_owned()))
+        );
+        assert_eq!(Field::parse("id3", "bpm"), Some(Err("bpm".to_owned())));
+    }
+}
@@ -0,0 +1,109 @@
+/* Built-in imports */
+use std::time::SystemTime;
+/* Dependencies */
+use chrono::{
+    format::{Item, StrftimeItems},
+    DateTime, Local,
+};
+
+use super::Padding;
+use crate::Metadata;
+
+/// Format of time placeholders without one.
+const DEFAULT_TIME_FORMAT: &str = "%Y-%m-%d";
+
+/// Placeholder pulling from the metadata of the file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Field {
+    /// `{mtime:%Y-%m-%d}`.
+    Modified(String),
+    /// `{ctime:%Y-%m-%d}`.
+    Changed(String),
+    /// `{atime:%Y-%m-%d}`.
+    Accessed(String),
+    /// `{size}`, in bytes, padded like counters.
+    Size(Padding),
+    /// `{owner}`.
+    Owner,
+    /// `{group}`.
+    Group,
+    /// `{mode}`.
+    Mode,
+}
+
+impl Field {
+    /// Parses the `{name:format}` placeholder, `None` if `name` isn't
+    /// a metadata placeholder, `Some(Err(format))` if `format` is invalid.
+    pub fn parse(name: &str, format: &str) -> Option<Result<Self, String>> {
+        let no_format = |field| {
+            if format.is_empty() {
+                Ok(field)
+            } else {
+                Err(format.to_owned())
+            }
+        };
+
+        Some(match name {
+            "mtime" => time_format(format).map(Self::Modified),
+            "ctime" => time_format(format).map(Self::Changed),
+            "atime" => time_format(format).map(Self::Accessed),
+            "size" => format.parse().map(Self::Size),
+            "owner" => no_format(Self::Owner),
+            "group" => no_format(Self::Group),
+            "mode" => no_format(Self::Mode),
+            _ => return None,
+        })
+    }
+
+    /// Value of the field, empty if unavailable on this platform.
+    pub fn render(&self, metadata: &Metadata) -> String {
+        let time = |time: Option<SystemTime>, format: &str| {
+            time.map(|found| {
+                DateTime::<Local>::from(found).format(format).to_string()
+            })
+        };
+        match self.clone() {
+            Self::Modified(format) => time(metadata.modified(), &format),
+            Self::Changed(format) => time(metadata.changed(), &format),
+            Self::Accessed(format) => time(metadata.accessed(), &format),
+            Self::Size(padding) => Some(padding.apply(metadata.size())),
+            Self::Owner => metadata.owner(),
+            Self::Group => metadata.group(),
+            Self::Mode => metadata.mode(),
+        }
+        .unwrap_or_default()
+    }
+}
+
+/// Validates a strftime `format`, defaulting to [`DEFAULT_TIME_FORMAT`].
+pub fn time_format(format: &str) -> Result<String, String> {
+    if format.is_empty() {
+        return Ok(DEFAULT_TIME_FORMAT.to_owned());
+    }
+    if StrftimeItems::new(format).any(|item| item == Item::Error) {
+        return Err(format.to_owned());
+    }
+    Ok(format.to_owned())
+}
+
+#[cfg(test)]
+#[allow(clippy::literal_string_with_formatting_args)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Field::parse("n", ""), None);
+        assert_eq!(
+            Field::parse("mtime", ""),
+            Some(Ok(Field::Modified("%Y-%m-%d".to_owned())))
+        );
+        assert_eq!(
+            Field::parse("ctime", "%Y%m%d_%H%M"),
+            Some(Ok(Field::Changed("%Y%m%d_%H%M".to_owned())))
+        );
+        assert_eq!(Field::parse("atime", "%Q"), Some(Err("%Q".to_owned())));
+        assert_eq!(Field::parse("owner", "x"), Some(Err("x".to_owned())));
+        assert_eq!(Field::parse("size", "x"), Some(Err("x".to_owned())));
+    }
+}
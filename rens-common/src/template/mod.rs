@@ -0,0 +1,507 @@
+/* Modules */
+mod case;
+mod hash;
+#[cfg(feature = "media")]
+mod media;
+mod metadata;
+mod sequence;
+/* Built-in imports */
+use core::{fmt, iter::Peekable, str, str::Chars, str::FromStr};
+use std::path::is_separator;
+/* Crate imports */
+use crate::File;
+use case::{Cased, Escape};
+use metadata::Field;
+/* Dependencies */
+use derive_more::Constructor;
+use regex::bytes::Captures;
+/* Re-exports */
+pub use case::Transform;
+pub use hash::{Algorithm, Digests};
+#[cfg(feature = "media")]
+pub use media::{Tag, Tags};
+pub use sequence::{Sequence, SortKey};
+
+/// Syntax of a [`Template`], also shown by `--help`.
+pub const SYNTAX: &str = "\
+Replacement, supporting placeholders on top of the regex syntax \
+(`$1`, `${name}`):
+
+- `{n:1}` is the position of the file in the sequence, `{n:04}` pads it \
+with zeros to 4 digits, `{n:4}` with spaces.
+- `{mtime:%Y-%m-%d}`, `{ctime}`, `{atime}` (strftime format), `{size}`, \
+`{owner}`, `{group}` and `{mode}` come from the file's metadata.
+- `{exif:Model}`, `{exif:DateTimeOriginal:%Y-%m-%d}`, `{id3:artist}` and \
+`{id3:track:02}` come from the tags embedded in photos and audio files \
+(MP3 and FLAC).
+- `{hash:sha256:8}` is the content's digest, truncated to 8 characters \
+[algorithms: md5, sha1, sha256 (default), sha512, blake3].
+- `{$1:snake}` is the capture group `1` (or a named one) with a transform \
+applied [transforms: upper, lower, snake, kebab, camel, pascal, title, nfc, \
+nfd, ascii].
+- `\\U`, `\\L`, `\\E`, `\\u` and `\\l` convert the case of what follows, \
+like GNU sed does.
+
+Path separators in metadata and tags are replaced by `_`.
+
+Note: `{`, `}` and `\\` used to be literal, they are now special \
+characters: use `{{`, `}}` and `\\\\` for literal ones.";
+
+/// Replacement string, supporting placeholders on top of the regex syntax
+/// (see [`SYNTAX`]).
+///
+/// Note: `${name}` is kept as is, being a regex capture group.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    source: String,
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    /// Expanded by the regex, may contain `$1` or `${name}`.
+    Literal(String),
+    Counter(Padding),
+    Metadata(Field),
+    Hash(hash::Field),
+    #[cfg(feature = "media")]
+    Media(media::Field),
+    Group(String, Option<Transform>),
+    Case(Escape),
+}
+
+/// Rust-like width, `04` pads with zeros while `4` pads with spaces.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Padding {
+    width: usize,
+    zeros: bool,
+}
+
+/// What placeholders are resolved from, besides the regex match.
+#[derive(Debug, Clone, Copy, Constructor)]
+#[non_exhaustive]
+pub struct Context<'ctx> {
+    pub file: &'ctx File,
+    /// Position of the file in the [`Sequence`].
+    pub counter: u64,
+    pub digests: &'ctx Digests,
+    #[cfg(feature = "media")]
+    pub tags: &'ctx Tags,
+}
+
+impl Template {
+    /// Replacement of the match `caps` in the name of `ctx.file`.
+    #[inline]
+    #[must_use]
+    pub fn render(&self, caps: &Captures, ctx: &Context) -> Vec<u8> {
+        let mut cased = Cased::default();
+        for segment in self.segments.iter().cloned() {
+            match segment {
+                Segment::Literal(literal) => {
+                    let mut expanded = Vec::new();
+                    caps.expand(literal.as_bytes(), &mut expanded);
+                    cased.push_bytes(&expanded);
+                },
+                Segment::Counter(padding) => {
+                    cased.push_str(&padding.apply(ctx.counter));
+                },
+                Segment::Metadata(field) => {
+                    cased.push_str(&without_separators(
+                        &field.render(ctx.file.metadata()),
+                    ));
+                },
+                Segment::Hash(field) => {
+                    cased.push_str(&field.render(ctx.file, ctx.digests));
+                },
+                #[cfg(feature = "media")]
+                Segment::Media(field) => {
+                    cased.push_str(&without_separators(
+                        &field.render(ctx.file, ctx.tags),
+                    ));
+                },
+                Segment::Group(group, transform) => {
+                    let bytes = group
+                        .parse()
+                        .map_or_else(
+                            |_err| caps.name(&group),
+                            |idx| caps.get(idx),
+                        )
+                        .map_or(&[][..], |found| found.as_bytes());
+                    // Transforms need text, invalid UTF-8 is kept as is
+                    match (transform, str::from_utf8(bytes)) {
+                        (Some(kind), Ok(text)) => {
+                            cased.push_str(&kind.apply(text));
+                        },
+                        (None | Some(_), _) => cased.push_bytes(bytes),
+                    }
+                },
+                Segment::Case(escape) => cased.apply(escape),
+            }
+        }
+        cased.into_bytes()
+    }
+
+    /// Algorithms used by `{hash}` placeholders.
+    #[inline]
+    #[must_use]
+    pub fn algorithms(&self) -> Vec<Algorithm> {
+        let mut algorithms = Vec::new();
+        for segment in &self.segments {
+            if let Segment::Hash(field) = *segment {
+                if !algorithms.contains(&field.algorithm()) {
+                    algorithms.push(field.algorithm());
+                }
+            }
+        }
+        algorithms
+    }
+
+    /// Tags used by media placeholders.
+    #[cfg(feature = "media")]
+    #[inline]
+    #[must_use]
+    pub fn tags(&self) -> Vec<Tag> {
+        let mut tags = Vec::new();
+        for segment in self.segments.iter().cloned() {
+            if let Segment::Media(field) = segment {
+                if !tags.contains(&field.tag()) {
+                    tags.push(field.tag());
+                }
+            }
+        }
+        tags
+    }
+}
+
+impl Padding {
+    fn apply(self, value: u64) -> String {
+        if self.zeros {
+            format!("{value:0width$}", width = self.width)
+        } else {
+            format!("{value:width$}", width = self.width)
+        }
+    }
+}
+
+impl FromStr for Padding {
+    type Err = String;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        if format.is_empty() {
+            return Ok(Self::default());
+        }
+        let width = format.parse().map_err(|_err| format.to_owned())?;
+        Ok(Self {
+            width,
+            zeros: format.starts_with('0'),
+        })
+    }
+}
+
+impl FromStr for Template {
+    type Err = Error;
+
+    #[inline]
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut chars = input.chars().peekable();
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut push = |segment, pending: &mut String| {
+            if !pending.is_empty() {
+                segments.push(Segment::Literal(pending.clone()));
+                pending.clear();
+            }
+            segments.push(segment);
+        };
+
+        while let Some(ch) = chars.next() {
+            match ch {
+                '\\' if chars.next_if_eq(&'\\').is_some() => literal.push(ch),
+                '\\' => match chars.peek().copied().and_then(Escape::from_char)
+                {
+                    Some(escape) => {
+                        chars.next();
+                        push(Segment::Case(escape), &mut literal);
+                    },
+                    None => literal.push(ch),
+                },
+                // regex escaped dollar, `$${n}` is a dollar followed by `{n}`
+                '$' if chars.next_if_eq(&'$').is_some() => {
+                    literal.push_str("$$");
+                },
+                // regex capture group
+                '$' if chars.peek() == Some(&'{') => {
+                    literal.push(ch);
+                    for cur in chars.by_ref() {
+                        literal.push(cur);
+                        if cur == '}' {
+                            break;
+                        }
+                    }
+                },
+                '{' if chars.next_if_eq(&'{').is_some() => literal.push('{'),
+                '}' if chars.next_if_eq(&'}').is_some() => literal.push('}'),
+                '}' => return Err(Self::Err::Unmatched),
+                '{' => push(parse_placeholder(&mut chars)?, &mut literal),
+                _ => literal.push(ch),
+            }
+        }
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        Ok(Self {
+            source: input.to_owned(),
+            segments,
+        })
+    }
+}
+
+/// Parses a `name:format}` placeholder, the opening brace being consumed.
+fn parse_placeholder(chars: &mut Peekable<Chars>) -> Result<Segment, Error> {
+    let mut placeholder = String::new();
+    loop {
+        match chars.next() {
+            None => return Err(Error::Unclosed),
+            Some('}') => break,
+            Some(ch) => placeholder.push(ch),
+        }
+    }
+    let (name, format) =
+        placeholder.split_once(':').unwrap_or((&placeholder, ""));
+
+    if let Some(group) = name.strip_prefix('$') {
+        let transform = (!format.is_empty())
+            .then(|| format.parse())
+            .transpose()
+            .map_err(|_err| {
+                Error::InvalidFormat(name.to_owned(), format.to_owned())
+            })?;
+        return Ok(Segment::Group(group.to_owned(), transform));
+    }
+
+    if let Some(field) = Field::parse(name, format) {
+        return field
+            .map(Segment::Metadata)
+            .map_err(|invalid| Error::InvalidFormat(name.to_owned(), invalid));
+    }
+
+    #[cfg(feature = "media")]
+    if let Some(field) = media::Field::parse(name, format) {
+        return field
+            .map(Segment::Media)
+            .map_err(|invalid| Error::InvalidFormat(name.to_owned(), invalid));
+    }
+
+    match name {
+        "hash" => hash::Field::parse(format)
+            .map(Segment::Hash)
+            .map_err(|invalid| Error::InvalidFormat(name.to_owned(), invalid)),
+        "n" => format
+            .parse()
+            .map(Segment::Counter)
+            .map_err(|invalid| Error::InvalidFormat(name.to_owned(), invalid)),
+        #[cfg(not(feature = "media"))]
+        "exif" | "id3" => Err(Error::MissingFeature(name.to_owned(), "media")),
+        _ => Err(Error::Unknown(name.to_owned())),
+    }
+}
+
+impl From<Transform> for Template {
+    /// `{$0:transform}`, the whole match transformed.
+    #[inline]
+    fn from(transform: Transform) -> Self {
+        Self {
+            source: format!("{{$0:{}}}", transform.to_string().to_lowercase()),
+            segments: vec![Segment::Group("0".to_owned(), Some(transform))],
+        }
+    }
+}
+
+impl fmt::Display for Template {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("Unclosed placeholder, use `{{{{` for a literal `{{`.")]
+    Unclosed,
+    #[error("Unmatched `}}`, use `}}}}` for a literal `}}`.")]
+    Unmatched,
+    #[error("Unknown placeholder: `{{{0}}}`.")]
+    Unknown(String),
+    #[error("`{{{0}}}` requires the `{1}` feature.")]
+    MissingFeature(String, &'static str),
+    #[error("Invalid format for `{{{0}}}`: `{1}`.")]
+    InvalidFormat(String, String),
+}
+
+/// `value` with path separators and NUL replaced by `_`, so values like
+/// `{mtime:%Y/%m}` or an `AC/DC` tag never move files.
+fn without_separators(value: &str) -> String {
+    value
+        .chars()
+        .map(|ch| {
+            if is_separator(ch) || ch == '\0' {
+                '_'
+            } else {
+                ch
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+// placeholders look like formatting arguments
+#[allow(clippy::literal_string_with_formatting_args)]
+mod tests {
+    use super::*;
+    use core::time::Duration;
+    use regex::bytes::Regex;
+    #[cfg(unix)]
+    use std::os::unix::fs::PermissionsExt as _;
+    use std::{fs, time::SystemTime};
+
+    /// Renders `template` for `holidayPhotos-Beach`, `name` being the first
+    /// group and `place` the second one.
+    fn render(template: &str, counter: u64) -> String {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("holidayPhotos-Beach");
+        fs::write(&path, "content").unwrap();
+        let file = fs::File::options().write(true).open(&path).unwrap();
+        // 2024-03-01T12:00:00Z, far enough from midnight for any timezone
+        file.set_modified(
+            SystemTime::UNIX_EPOCH + Duration::from_secs(1_709_294_400),
+        )
+        .unwrap();
+        #[cfg(unix)]
+        file.set_permissions(fs::Permissions::from_mode(0o640))
+            .unwrap();
+
+        let regex = Regex::new(r"(?<name>\w+)-(?<place>\w+)").unwrap();
+        let caps = regex.captures(b"holidayPhotos-Beach").unwrap();
+        let ctx = Context {
+            file: &File::from_path(path).unwrap(),
+            counter,
+            digests: &Digests::default(),
+            #[cfg(feature = "media")]
+            tags: &Tags::default(),
+        };
+        let rendered =
+            Template::from_str(template).unwrap().render(&caps, &ctx);
+        String::from_utf8(rendered).unwrap()
+    }
+
+    #[test]
+    fn test_no_placeholders() {
+        for template in ["", "plain", "a$", "a\\b"] {
+            assert_eq!(render(template, 7), template);
+        }
+        assert_eq!(render("${2}_${name}", 7), "Beach_holidayPhotos");
+        assert_eq!(render("$$", 7), "$");
+    }
+
+    #[test]
+    fn test_counter() {
+        assert_eq!(render("IMG_{n}", 7), "IMG_7");
+        assert_eq!(render("IMG_{n:04}", 7), "IMG_0007");
+        assert_eq!(render("IMG_{n:4}", 7), "IMG_   7");
+        assert_eq!(render("{n:02}-$2", 123), "123-Beach");
+        assert_eq!(render("$${n}", 3), "$3");
+    }
+
+    #[test]
+    fn test_escaped_braces() {
+        assert_eq!(render("{{n}}", 3), "{n}");
+        assert_eq!(render("${2}{{{n}}}", 3), "Beach{3}");
+        assert_eq!(render("\\\\U$2", 3), "\\UBeach");
+    }
+
+    #[test]
+    fn test_case_escapes() {
+        assert_eq!(render("\\U$1\\E-$2", 0), "HOLIDAYPHOTOS-Beach");
+        assert_eq!(render("\\L$0", 0), "holidayphotos-beach");
+        assert_eq!(render("\\u${1}_\\l$2", 0), "HolidayPhotos_beach");
+        assert_eq!(render("\\u\\L${name}", 0), "Holidayphotos");
+        assert_eq!(render("\\Uimg_{n:02}", 1), "IMG_01");
+    }
+
+    #[test]
+    fn test_transforms() {
+        assert_eq!(render("{$1:snake}", 0), "holiday_photos");
+        assert_eq!(
+            render("{$name:title} ({$place:upper})", 0),
+            "Holiday Photos (BEACH)"
+        );
+        assert_eq!(render("{$2}{$missing:kebab}", 0), "Beach");
+        assert_eq!(render("\\U{$1:kebab}", 0), "HOLIDAY-PHOTOS");
+    }
+
+    #[test]
+    fn test_metadata() {
+        assert_eq!(render("{mtime}_$0", 0), "2024-03-01_holidayPhotos-Beach");
+        assert_eq!(render("{mtime:%Y%m}", 0), "202403");
+        assert_eq!(render("{mtime:%Y/%m}", 0), "2024_03");
+        assert_eq!(without_separators("AC/DC\0"), "AC_DC_");
+        assert_eq!(render("{size}-{size:03}", 0), "7-007");
+        #[cfg(unix)]
+        {
+            assert_eq!(render("{mode}", 0), "640");
+            assert!(!render("{owner}:{group}", 0).starts_with(':'));
+        }
+    }
+
+    #[test]
+    fn test_hash() {
+        // sha256 of "content"
+        assert_eq!(render("{hash:8}.$2", 0), "ed7002b4.Beach");
+        let template =
+            Template::from_str("{hash:md5}{hash:4}{hash:8}").unwrap();
+        assert_eq!(template.algorithms(), [Algorithm::Md5, Algorithm::Sha256]);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(Template::from_str("{n"), Err(Error::Unclosed));
+        assert_eq!(Template::from_str("n}"), Err(Error::Unmatched));
+        assert_eq!(
+            Template::from_str("{x}"),
+            Err(Error::Unknown("x".to_owned()))
+        );
+        assert_eq!(
+            Template::from_str("{n:ab}"),
+            Err(Error::InvalidFormat("n".to_owned(), "ab".to_owned()))
+        );
+        assert_eq!(
+            Template::from_str("{mtime:%Q}"),
+            Err(Error::InvalidFormat("mtime".to_owned(), "%Q".to_owned()))
+        );
+        assert_eq!(
+            Template::from_str("{$1:shout}"),
+            Err(Error::InvalidFormat("$1".to_owned(), "shout".to_owned()))
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "media"))]
+    fn test_missing_feature() {
+        assert_eq!(
+            Template::from_str("{exif:Model}"),
+            Err(Error::MissingFeature("exif".to_owned(), "media"))
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let source = "IMG_{n:04}_${1}_\\U{$2:snake}";
+        assert_eq!(Template::from_str(source).unwrap().to_string(), source);
+        assert_eq!(
+            Template::from(Transform::Nfc),
+            Template::from_str("{$0:nfc}").unwrap()
+        );
+    }
+}
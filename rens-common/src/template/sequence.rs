@@ -0,0 +1,186 @@
+/* Built-in imports */
+use core::{cmp::Ordering, iter::Peekable, str::Chars};
+use std::{collections::HashMap, path::PathBuf};
+/* Dependencies */
+use derive_more::{Display, FromStr};
+use serde::{Deserialize, Serialize};
+
+use crate::File;
+
+/// Numbering of the files of a batch, used by `{n}` placeholders.
+#[derive(Debug, Clone)]
+pub struct Sequence {
+    start: u64,
+    step: u64,
+    positions: HashMap<PathBuf, u64>,
+}
+
+impl Sequence {
+    /// Numbers `files` in the given order.
+    #[inline]
+    pub fn new<'file, I>(start: u64, step: u64, files: I) -> Self
+    where
+        I: IntoIterator<Item = &'file File>,
+    {
+        let positions = files.into_iter().map(File::path).zip(0..).collect();
+        Self {
+            start,
+            step,
+            positions,
+        }
+    }
+
+    /// Number of `file`, files outside of the sequence get the first one.
+    #[inline]
+    #[must_use]
+    pub fn number(&self, file: &File) -> u64 {
+        let position = self
+            .positions
+            .get(&file.path())
+            .copied()
+            .unwrap_or_default();
+        self.start
+            .saturating_add(self.step.saturating_mul(position))
+    }
+}
+
+impl Default for Sequence {
+    #[inline]
+    fn default() -> Self {
+        Self::new(1, 1, [])
+    }
+}
+
+#[derive(
+    Debug,
+    Default,
+    Display,
+    Clone,
+    Copy,
+    FromStr,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+#[non_exhaustive]
+pub enum SortKey {
+    /// Path, character by character.
+    #[default]
+    Name,
+    /// Modification time, oldest first.
+    Mtime,
+    /// Size, smallest first.
+    Size,
+    /// Path, with numbers compared by value (`2` before `10`).
+    Natural,
+}
+
+impl SortKey {
+    /// Sorts `files`, ties are ordered by name.
+    #[inline]
+    pub fn sort(self, files: &mut [File]) {
+        files.sort_by_cached_key(File::path);
+        match self {
+            Self::Name => {},
+            Self::Mtime => {
+                files.sort_by_key(|file| file.metadata().modified());
+            },
+            Self::Size => files.sort_by_key(|file| file.metadata().size()),
+            Self::Natural => files.sort_by(|lhs, rhs| {
+                natural_cmp(
+                    &lhs.path().to_string_lossy(),
+                    &rhs.path().to_string_lossy(),
+                )
+            }),
+        }
+    }
+}
+
+/// Compares strings with digit runs compared by value.
+fn natural_cmp(lhs: &str, rhs: &str) -> Ordering {
+    let mut lhs_chars = lhs.chars().peekable();
+    let mut rhs_chars = rhs.chars().peekable();
+    loop {
+        let ordering = match (lhs_chars.peek(), rhs_chars.peek()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(lhs_ch), Some(rhs_ch))
+                if lhs_ch.is_ascii_digit() && rhs_ch.is_ascii_digit() =>
+            {
+                let number = |chars: &mut Peekable<Chars>| {
+                    let mut digits = String::new();
+                    while let Some(digit) = chars.next_if(char::is_ascii_digit)
+                    {
+                        digits.push(digit);
+                    }
+                    digits.trim_start_matches('0').to_owned()
+                };
+                let (lhs_num, rhs_num) =
+                    (number(&mut lhs_chars), number(&mut rhs_chars));
+                lhs_num
+                    .len()
+                    .cmp(&rhs_num.len())
+                    .then_with(|| lhs_num.cmp(&rhs_num))
+            },
+            (Some(lhs_ch), Some(rhs_ch)) => {
+                let ordering = lhs_ch.cmp(rhs_ch);
+                lhs_chars.next();
+                rhs_chars.next();
+                ordering
+            },
+        };
+        if ordering.is_ne() {
+            return ordering;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_natural_cmp() {
+        assert_eq!(natural_cmp("img2", "img10"), Ordering::Less);
+        assert_eq!(natural_cmp("img10", "img2"), Ordering::Greater);
+        assert_eq!(natural_cmp("img02", "img2"), Ordering::Equal);
+        assert_eq!(natural_cmp("a10b2", "a10b10"), Ordering::Less);
+        assert_eq!(natural_cmp("a", "a1"), Ordering::Less);
+        assert_eq!(natural_cmp("b1", "a2"), Ordering::Greater);
+    }
+
+    #[test]
+    fn test_sequence() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut files = ["img10", "img2", "img1"]
+            .into_iter()
+            .map(|name| {
+                let path = dir.path().join(name);
+                fs::write(&path, name).unwrap();
+                File::from_path(path).unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        SortKey::Natural.sort(&mut files);
+        let sequence = Sequence::new(10, 5, &files);
+        let numbers = files
+            .iter()
+            .map(|file| (file.name().to_string(), sequence.number(file)))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            numbers,
+            [
+                ("img1".to_owned(), 10),
+                ("img2".to_owned(), 15),
+                ("img10".to_owned(), 20)
+            ]
+        );
+
+        SortKey::Size.sort(&mut files);
+        assert_eq!(files.first().unwrap().name().to_string(), "img1");
+    }
+}
@@ -1,5 +1,5 @@
 /* Built-in imports */
-use core::iter::{self, FlatMap, Map};
+use core::iter::{self, FlatMap, Inspect, Map};
 /* Dependencies */
 use either::Either;
 
@@ -8,11 +8,8 @@ pub trait IteratorExt: Iterator + Sized {
     fn tap_for_each(
         self,
         func: impl Fn(&Self::Item),
-    ) -> Map<Self, impl FnMut(Self::Item) -> Self::Item> {
-        self.map(move |item| {
-            func(&item);
-            item
-        })
+    ) -> Inspect<Self, impl FnMut(&Self::Item)> {
+        self.inspect(func)
     }
 
     #[inline]
@@ -25,6 +22,7 @@ pub trait IteratorExt: Iterator + Sized {
     }
 
     #[inline]
+    #[allow(clippy::type_complexity)]
     fn flat_map_if<I>(
         self,
         condition: impl Fn(&Self::Item) -> bool,
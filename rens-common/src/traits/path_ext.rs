@@ -1,6 +1,6 @@
 /* Built-in imports */
 #[cfg(unix)]
-use std::os::unix::fs::FileTypeExt;
+use std::os::unix::fs::FileTypeExt as _;
 #[cfg(windows)]
 use std::os::windows::fs::{FileTypeExt, MetadataExt};
 use std::{fs, io, path::Path};
@@ -18,31 +18,37 @@ pub enum Kind {
     Other,
 }
 
-pub trait PathExt: AsRef<Path> {
+impl From<&fs::Metadata> for Kind {
     #[inline]
-    fn kind(&self) -> io::Result<Kind> {
-        let metadata = fs::symlink_metadata(self)?;
+    fn from(metadata: &fs::Metadata) -> Self {
         if metadata.is_dir() {
-            return Ok(Kind::Directory);
+            return Self::Directory;
         }
         if metadata.file_type().is_symlink() {
-            return Ok(Kind::Symlink);
+            return Self::Symlink;
         }
         #[cfg(unix)]
         if metadata.file_type().is_socket() {
-            return Ok(Kind::UnixSocket);
+            return Self::UnixSocket;
         }
         #[cfg(windows)]
         if metadata.file_type().is_symlink_dir()
             || metadata.file_type().is_symlink_file()
         {
-            return Ok(Kind::Symlink);
+            return Self::Symlink;
         }
         if metadata.is_file() {
-            return Ok(Kind::File);
+            return Self::File;
         }
 
-        Ok(Kind::Other)
+        Self::Other
+    }
+}
+
+pub trait PathExt: AsRef<Path> {
+    #[inline]
+    fn kind(&self) -> io::Result<Kind> {
+        fs::symlink_metadata(self).map(|metadata| Kind::from(&metadata))
     }
 
     #[inline]
@@ -0,0 +1,112 @@
+//! Escaping used for TSV fields, so they can't contain separators.
+
+/* Built-in imports */
+use std::path::{Path, PathBuf};
+/* Crate imports */
+use crate::os_bytes;
+/* Dependencies */
+use bstr::ByteSlice;
+
+/// Escapes backslashes, tabs and line breaks.
+#[inline]
+#[must_use]
+pub fn escape(field: &str) -> String {
+    field
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+/// Reverts [`escape`], `None` if `field` contains an unknown escape sequence.
+#[inline]
+#[must_use]
+pub fn unescape(field: &str) -> Option<String> {
+    String::from_utf8(unescape_bytes(field)?).ok()
+}
+
+/// Escapes `path` like [`escape`], bytes that aren't valid UTF-8 as `\xNN`.
+#[inline]
+#[must_use]
+pub fn escape_path(path: &Path) -> String {
+    let bytes = os_bytes::from_os_str(path.as_os_str());
+    let mut escaped = String::with_capacity(bytes.len());
+    for chunk in ByteSlice::utf8_chunks(bytes.as_slice()) {
+        escaped.push_str(&escape(chunk.valid()));
+        for &byte in chunk.invalid() {
+            os_bytes::push_hex(&mut escaped, byte);
+        }
+    }
+    escaped
+}
+
+/// Reverts [`escape_path`].
+#[inline]
+#[must_use]
+pub fn unescape_path(field: &str) -> Option<PathBuf> {
+    unescape_bytes(field)
+        .map(|bytes| PathBuf::from(os_bytes::into_os_string(bytes)))
+}
+
+fn unescape_bytes(field: &str) -> Option<Vec<u8>> {
+    let mut chars = field.chars();
+    let mut buf = Vec::with_capacity(field.len());
+    while let Some(ch) = chars.next() {
+        let unescaped = match ch {
+            '\\' => match chars.next()? {
+                '\\' => '\\',
+                't' => '\t',
+                'n' => '\n',
+                'r' => '\r',
+                'x' => {
+                    let digits = chars.by_ref().take(2).collect::<String>();
+                    if digits.len() != 2
+                        || !digits
+                            .chars()
+                            .all(|digit| digit.is_ascii_hexdigit())
+                    {
+                        return None;
+                    }
+                    buf.push(u8::from_str_radix(&digits, 16).ok()?);
+                    continue;
+                },
+                _ => return None,
+            },
+            _ => ch,
+        };
+        buf.extend_from_slice(unescaped.encode_utf8(&mut [0; 4]).as_bytes());
+    }
+    Some(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        for field in ["plain", "a\tb", "a\\tb", "line\nbreak\r", "\\"] {
+            assert!(!escape(field).contains(['\t', '\n', '\r']));
+            assert_eq!(unescape(&escape(field)).unwrap(), field);
+        }
+    }
+
+    #[test]
+    fn test_invalid_escape() {
+        assert_eq!(unescape("a\\qb"), None);
+        assert_eq!(unescape("trailing\\"), None);
+        assert_eq!(unescape_path("a\\xZZ"), None);
+        assert_eq!(unescape_path("a\\xE"), None);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_non_utf8_path() {
+        let path =
+            PathBuf::from(os_bytes::into_os_string(b"caf\xe9\t\\xE9".to_vec()));
+        let escaped = escape_path(&path);
+        assert_eq!(escaped, "caf\\xE9\\t\\\\xE9");
+        assert_eq!(unescape_path(&escaped).unwrap(), path);
+        assert_eq!(unescape(&escaped), None);
+    }
+}
@@ -0,0 +1,265 @@
+/* Built-in imports */
+use core::iter;
+use std::path::{Path, PathBuf};
+/* Crate imports */
+use crate::traits::{FileKind, PathExt as _};
+/* Dependencies */
+use derive_more::Constructor;
+use either::Either;
+use ignore::{
+    overrides::{Override, OverrideBuilder},
+    DirEntry, Match, WalkBuilder,
+};
+use regex::Regex;
+
+/// Turns the paths given by the user into the paths to rename.
+#[derive(Debug, Default, Clone, Copy, Constructor)]
+#[non_exhaustive]
+pub struct Walker {
+    /// Replace directories by their content, themselves excluded.
+    pub recursive: bool,
+    /// How deep to go, `None` meaning as deep as possible.
+    pub depth: Option<usize>,
+    pub allow_hidden: bool,
+    /// Follow `.gitignore` (local and global), `.ignore`
+    /// and `.git/info/exclude` files.
+    pub auto_ignore: bool,
+}
+
+impl Walker {
+    #[inline]
+    pub fn walk<I>(
+        self,
+        paths: I,
+    ) -> impl Iterator<Item = Result<PathBuf, ignore::Error>>
+    where
+        I: IntoIterator<Item = PathBuf>,
+    {
+        self.walk_pruned(paths, None)
+    }
+
+    /// Walks like [`Walker::walk`], only yielding the paths matching `filter`.
+    ///
+    /// Note: directories excluded by a glob aren't traversed.
+    #[inline]
+    pub fn walk_filtered<I>(
+        self,
+        paths: I,
+        filter: Filter,
+    ) -> impl Iterator<Item = Result<PathBuf, ignore::Error>>
+    where
+        I: IntoIterator<Item = PathBuf>,
+    {
+        self.walk_pruned(paths, Some(filter.clone()))
+            .filter(move |walked| {
+                walked.as_ref().map_or(true, |path| filter.matches(path))
+            })
+    }
+
+    fn walk_pruned<I>(
+        self,
+        paths: I,
+        filter: Option<Filter>,
+    ) -> impl Iterator<Item = Result<PathBuf, ignore::Error>>
+    where
+        I: IntoIterator<Item = PathBuf>,
+    {
+        paths.into_iter().flat_map(move |path| {
+            if self.recursive && path.is_dir() {
+                Either::Left(self.children(path, filter.clone()))
+            } else {
+                Either::Right(iter::once(Ok(path)))
+            }
+        })
+    }
+
+    fn children(
+        self,
+        path: PathBuf,
+        filter: Option<Filter>,
+    ) -> impl Iterator<Item = Result<PathBuf, ignore::Error>> {
+        let mut builder = WalkBuilder::new(&path);
+        if let Some(pruner) = filter {
+            builder.filter_entry(move |entry| {
+                !(entry.file_type().is_some_and(|kind| kind.is_dir())
+                    && pruner.globs.matched(entry.path(), true).is_ignore())
+            });
+        }
+        builder
+            .hidden(!self.allow_hidden)
+            .max_depth(self.depth)
+            .require_git(false) // dunno
+            .git_exclude(self.auto_ignore)
+            .git_global(self.auto_ignore)
+            .git_ignore(self.auto_ignore)
+            .ignore(self.auto_ignore);
+        // assume that parsing ignore files also means that we want to ignore
+        // the `.git` dir and `.gitignore` files themselves
+        if self.auto_ignore {
+            #[allow(clippy::expect_used)]
+            builder.overrides(
+                OverrideBuilder::new(path)
+                    .add("!/.git/")
+                    .expect("unreachable")
+                    .add("!/.gitignore")
+                    .expect("unreachable")
+                    .build()
+                    .expect("unreachable"),
+            );
+        }
+        builder
+            .build()
+            // The walked directory isn't part of its content
+            .filter(|entry| {
+                entry.as_ref().map_or(true, |found| found.depth() > 0)
+            })
+            .map(|entry| entry.map(DirEntry::into_path))
+    }
+}
+
+/// Restricts the walked paths by globs, a regex and their kind.
+#[derive(Debug, Clone)]
+pub struct Filter {
+    /// `--include` globs and negated `--exclude` globs.
+    globs: Override,
+    path_regex: Option<Regex>,
+    /// Accepted kinds, any if empty.
+    kinds: Vec<FileKind>,
+}
+
+impl Filter {
+    /// Globs follow the `.gitignore` syntax, relative to `root`.
+    ///
+    /// When some `include` globs are given, only the paths matching
+    /// one of them are kept, unless excluded.
+    #[inline]
+    pub fn new(
+        root: &Path,
+        include: &[String],
+        exclude: &[String],
+    ) -> Result<Self, ignore::Error> {
+        let mut builder = OverrideBuilder::new(root);
+        for glob in include {
+            builder.add(glob)?;
+        }
+        for glob in exclude {
+            builder.add(&format!("!{glob}"))?;
+        }
+        Ok(Self {
+            globs: builder.build()?,
+            path_regex: None,
+            kinds: Vec::new(),
+        })
+    }
+
+    /// Only keeps the paths matching `regex`.
+    #[inline]
+    #[must_use]
+    pub fn with_path_regex(self, regex: Regex) -> Self {
+        Self {
+            path_regex: Some(regex),
+            ..self
+        }
+    }
+
+    /// Only keeps the paths of one of the `kinds`.
+    #[inline]
+    #[must_use]
+    pub fn with_kinds(self, kinds: Vec<FileKind>) -> Self {
+        Self { kinds, ..self }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn matches(&self, path: &Path) -> bool {
+        let kind = path.kind().ok();
+        let globbed =
+            match self.globs.matched(path, kind == Some(FileKind::Directory)) {
+                Match::None => self.globs.num_whitelists() == 0,
+                Match::Ignore(_) => false,
+                Match::Whitelist(_) => true,
+            };
+        globbed
+            && self
+                .path_regex
+                .as_ref()
+                .map_or(true, |regex| regex.is_match(&path.to_string_lossy()))
+            && (self.kinds.is_empty()
+                || kind.is_some_and(|found| self.kinds.contains(&found)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_filtered_walk() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("photos").join("raw")).unwrap();
+        fs::create_dir_all(root.join("cache")).unwrap();
+        for name in [
+            "a.jpg",
+            "b.png",
+            "photos/c.jpg",
+            "photos/raw/d.jpg",
+            "cache/e.jpg",
+        ] {
+            fs::write(root.join(name), "").unwrap();
+        }
+        let walked = |filter: Filter| {
+            let mut found = Walker::new(true, None, false, false)
+                .walk_filtered([root.to_owned()], filter)
+                .map(|path| {
+                    path.unwrap()
+                        .strip_prefix(root)
+                        .unwrap()
+                        .to_string_lossy()
+                        .into_owned()
+                })
+                .collect::<Vec<_>>();
+            found.sort();
+            found
+        };
+
+        let globs = Filter::new(
+            root,
+            &["*.jpg".to_owned()],
+            &["cache".to_owned(), "raw/".to_owned()],
+        )
+        .unwrap();
+        assert_eq!(walked(globs.clone()), ["a.jpg", "photos/c.jpg"]);
+        assert_eq!(
+            walked(globs.with_path_regex(Regex::new("photos/[a-z]").unwrap())),
+            ["photos/c.jpg"]
+        );
+        // The root itself isn't walked
+        assert_eq!(
+            walked(
+                Filter::new(root, &[], &[])
+                    .unwrap()
+                    .with_kinds(vec![FileKind::Directory])
+            ),
+            ["cache", "photos", "photos/raw"]
+        );
+        Filter::new(root, &["a{".to_owned()], &[]).unwrap_err();
+    }
+
+    #[test]
+    fn test_hidden() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a"), "").unwrap();
+        fs::write(dir.path().join(".b"), "").unwrap();
+        let walked = |allow_hidden| {
+            Walker::new(true, None, allow_hidden, false)
+                .walk([dir.path().to_owned()])
+                .map(Result::unwrap)
+                .count()
+        };
+
+        assert_eq!(walked(false), 1);
+        assert_eq!(walked(true), 2);
+    }
+}
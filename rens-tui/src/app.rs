@@ -0,0 +1,964 @@
+/* Built-in imports */
+use core::ops::Range;
+use std::{
+    env,
+    path::{self, PathBuf},
+};
+/* Crate imports */
+use crate::cli::Mode;
+/* Dependencies */
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use regex::bytes::Regex;
+#[cfg(feature = "media")]
+use rens_common::template::{Tag, Tags};
+use rens_common::{
+    options::pattern::Options as PatternOpt,
+    os_bytes,
+    plan::Conflict,
+    template::{Algorithm, Digests, Sequence},
+    File, Plan, Rename, RenameTarget, Strategy, COMPOUND_EXTENSIONS,
+};
+
+/// Targets, in the order they are cycled through.
+const TARGETS: [RenameTarget; 5] = [
+    RenameTarget::Both,
+    RenameTarget::Stem,
+    RenameTarget::Extension,
+    RenameTarget::Extensions,
+    RenameTarget::Path,
+];
+
+/// Rows moved by page up/down.
+const PAGE: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Focus {
+    Pattern,
+    With,
+    Files,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    Info(String),
+    Error(String),
+}
+
+/// Next target after `target`, wrapping around.
+fn next_target(target: RenameTarget) -> RenameTarget {
+    TARGETS
+        .iter()
+        .skip_while(|&&cur| cur != target)
+        .nth(1)
+        .copied()
+        .unwrap_or(RenameTarget::Both)
+}
+
+#[derive(Debug)]
+pub struct Entry {
+    file: File,
+    enabled: bool,
+    /// Overrides the target of the app.
+    target: Option<RenameTarget>,
+    /// Hand-edited new name, escaped like [`os_bytes::escape`], wins over
+    /// the pattern.
+    edited: Option<String>,
+}
+
+impl Entry {
+    const fn new(file: File) -> Self {
+        Self {
+            file,
+            enabled: true,
+            target: None,
+            edited: None,
+        }
+    }
+
+    pub const fn file(&self) -> &File {
+        &self.file
+    }
+
+    pub const fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub const fn target(&self) -> Option<RenameTarget> {
+        self.target
+    }
+
+    pub const fn edited(&self) -> bool {
+        self.edited.is_some()
+    }
+}
+
+/// Parsed inputs.
+struct Compiled {
+    regex: Regex,
+    limit: usize,
+    strategy: Strategy,
+}
+
+impl Compiled {
+    /// New path of `file`, `None` if it's left untouched.
+    fn renamed(
+        &self,
+        file: &File,
+        target: RenameTarget,
+    ) -> Result<Option<PathBuf>, String> {
+        let renamed = file
+            .renamed_path(&self.strategy, target)
+            .map_err(|err| err.to_string())?;
+        Ok((renamed != file.path()).then_some(renamed))
+    }
+
+    /// Matches of the pattern within the `target` of `file`'s name.
+    fn matches(&self, file: &File, target: RenameTarget) -> Vec<Range<usize>> {
+        let name = file.name().to_bytes();
+        let Some(range) = file
+            .name()
+            .range(target, self.strategy.compound_extensions())
+        else {
+            return Vec::new();
+        };
+        let offset = range.start;
+        self.regex
+            .find_iter(name.get(range).unwrap_or_default())
+            .take(if self.limit == 0 {
+                usize::MAX
+            } else {
+                self.limit
+            })
+            .map(|found| {
+                found.start().saturating_add(offset)
+                    ..found.end().saturating_add(offset)
+            })
+            .collect()
+    }
+}
+
+/// Preview of an [`Entry`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Row {
+    /// Matches of the pattern, as byte ranges of the current name.
+    matches: Vec<Range<usize>>,
+    /// `None` if the name is left untouched.
+    to: Option<PathBuf>,
+    conflict: Option<Conflict>,
+}
+
+impl Row {
+    pub fn matches(&self) -> &[Range<usize>] {
+        &self.matches
+    }
+
+    pub const fn to(&self) -> Option<&PathBuf> {
+        self.to.as_ref()
+    }
+
+    pub const fn conflict(&self) -> Option<Conflict> {
+        self.conflict
+    }
+}
+
+#[derive(Debug)]
+pub struct App {
+    entries: Vec<Entry>,
+    mode: Mode,
+    target: RenameTarget,
+    pattern: String,
+    with: String,
+    options: PatternOpt,
+    compound: Vec<String>,
+    focus: Focus,
+    selected: usize,
+    /// New name being typed for the selected entry.
+    editing: Option<String>,
+    /// One row per entry, or why the inputs are invalid.
+    preview: Result<Vec<Row>, String>,
+    /// Algorithms `digests` were computed with.
+    hashed: Vec<Algorithm>,
+    digests: Digests,
+    /// Media tags `tags` were read for.
+    #[cfg(feature = "media")]
+    tagged: Vec<Tag>,
+    #[cfg(feature = "media")]
+    tags: Tags,
+    status: Option<Status>,
+    confirming: bool,
+    quit: bool,
+}
+
+impl App {
+    pub fn new(
+        files: Vec<File>,
+        mode: Mode,
+        target: RenameTarget,
+        pattern: String,
+        with: String,
+    ) -> Self {
+        let mut app = Self {
+            entries: files.into_iter().map(Entry::new).collect(),
+            mode,
+            target,
+            pattern,
+            with,
+            options: PatternOpt::default(),
+            compound: COMPOUND_EXTENSIONS.map(str::to_owned).to_vec(),
+            focus: Focus::Pattern,
+            selected: 0,
+            editing: None,
+            preview: Ok(Vec::new()),
+            hashed: Vec::new(),
+            digests: Digests::default(),
+            #[cfg(feature = "media")]
+            tagged: Vec::new(),
+            #[cfg(feature = "media")]
+            tags: Tags::default(),
+            status: None,
+            confirming: false,
+            quit: false,
+        };
+        app.refresh();
+        app
+    }
+
+    /// Applies the pattern `options` to the string and regex modes.
+    pub fn with_pattern_options(mut self, options: PatternOpt) -> Self {
+        self.options = options;
+        self.refresh();
+        self
+    }
+
+    /// Treats the `compound` extensions, like `tar.gz`, as a single one.
+    pub fn with_compound_extensions(mut self, compound: Vec<String>) -> Self {
+        self.compound = compound;
+        self.refresh();
+        self
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    pub const fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    pub const fn target(&self) -> RenameTarget {
+        self.target
+    }
+
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    pub fn with(&self) -> &str {
+        &self.with
+    }
+
+    pub const fn focus(&self) -> Focus {
+        self.focus
+    }
+
+    pub const fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn editing(&self) -> Option<&str> {
+        self.editing.as_deref()
+    }
+
+    pub const fn preview(&self) -> Result<&Vec<Row>, &String> {
+        self.preview.as_ref()
+    }
+
+    pub const fn status(&self) -> Option<&Status> {
+        self.status.as_ref()
+    }
+
+    pub fn set_status(&mut self, status: Status) {
+        self.status = Some(status);
+    }
+
+    pub const fn should_quit(&self) -> bool {
+        self.quit
+    }
+
+    /// Enabled renames, along with the index of their entry.
+    fn renames(&self) -> Vec<(usize, Rename)> {
+        let Ok(rows) = self.preview.as_ref() else {
+            return Vec::new();
+        };
+        self.entries
+            .iter()
+            .zip(rows)
+            .enumerate()
+            .filter(|&(_, (entry, _))| entry.enabled)
+            .filter_map(|(idx, (entry, row))| {
+                let target = entry.target.unwrap_or(self.target);
+                let moves =
+                    entry.edited.is_none() && target == RenameTarget::Path;
+                row.to.clone().map(|to| {
+                    (
+                        idx,
+                        Rename::new(entry.file.clone(), to)
+                            .creating_dirs(moves),
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Recomputes the preview from the inputs.
+    pub fn refresh(&mut self) {
+        self.preview = self.rows();
+        let (indexes, enabled): (Vec<_>, Vec<_>) =
+            self.renames().into_iter().unzip();
+        let plan = Plan::from_iter(enabled);
+        let conflicts = plan
+            .with_conflicts()
+            .map(|(_, conflict)| conflict)
+            .collect::<Vec<_>>();
+        if let Ok(rows) = self.preview.as_mut() {
+            for (idx, conflict) in indexes.into_iter().zip(conflicts) {
+                if let Some(row) = rows.get_mut(idx) {
+                    row.conflict = conflict;
+                }
+            }
+        }
+    }
+
+    fn compile(&mut self) -> Result<Option<Compiled>, String> {
+        if self.pattern.is_empty() {
+            return Ok(None);
+        }
+        let (regex, with, limit) =
+            self.mode.parse(&self.pattern, &self.with, &self.options)?;
+        let unnumbered = Strategy::new(regex.clone(), with, limit);
+
+        // Hash files and read their tags once per file set
+        let files = || {
+            self.entries
+                .iter()
+                .map(|entry| entry.file.clone())
+                .collect::<Vec<_>>()
+        };
+        let algorithms = unnumbered
+            .algorithms()
+            .into_iter()
+            .filter(|algo| !self.hashed.contains(algo))
+            .collect::<Vec<_>>();
+        if !algorithms.is_empty() {
+            self.digests.merge(Digests::compute(&files(), &algorithms));
+            self.hashed.extend(algorithms);
+        }
+        #[cfg(feature = "media")]
+        {
+            let tags = unnumbered
+                .tags()
+                .into_iter()
+                .filter(|tag| !self.tagged.contains(tag))
+                .collect::<Vec<_>>();
+            if !tags.is_empty() {
+                self.tags.merge(Tags::read(&files(), &tags));
+                self.tagged.extend(tags);
+            }
+        }
+        let sequence = Sequence::new(
+            1,
+            1,
+            self.entries
+                .iter()
+                .filter(|entry| entry.enabled)
+                .map(|entry| &entry.file),
+        );
+        let configured = unnumbered
+            .with_compound_extensions(self.compound.clone())
+            .with_sequence(sequence)
+            .with_digests(self.digests.clone())
+            .with_base(env::current_dir().map_err(|err| err.to_string())?);
+        #[cfg(feature = "media")]
+        let strategy = configured.with_tags(self.tags.clone());
+        #[cfg(not(feature = "media"))]
+        let strategy = configured;
+
+        Ok(Some(Compiled {
+            regex,
+            limit,
+            strategy,
+        }))
+    }
+
+    fn rows(&mut self) -> Result<Vec<Row>, String> {
+        let compiled = self.compile()?;
+
+        self.entries
+            .iter()
+            .map(|entry| {
+                let target = entry.target.unwrap_or(self.target);
+                let to = match (entry.edited.as_ref(), compiled.as_ref()) {
+                    (Some(name), _) => (*name != entry.file.name().to_string())
+                        .then(|| os_bytes::unescape(name))
+                        .flatten()
+                        .map(|bytes| {
+                            entry
+                                .file
+                                .parent()
+                                .join(os_bytes::into_os_string(bytes))
+                        }),
+                    (None, Some(parsed)) => {
+                        parsed.renamed(&entry.file, target)?
+                    },
+                    (None, None) => None,
+                };
+                Ok(Row {
+                    matches: compiled
+                        .as_ref()
+                        .map(|parsed| parsed.matches(&entry.file, target))
+                        .unwrap_or_default(),
+                    to,
+                    conflict: None,
+                })
+            })
+            .collect()
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        if self.editing.is_some() {
+            self.handle_edit_key(key);
+            return;
+        }
+        if key.code != KeyCode::Enter {
+            self.confirming = false;
+        }
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Char('c') if ctrl => self.quit = true,
+            KeyCode::Esc => self.quit = true,
+            KeyCode::Char('t') if ctrl => {
+                self.target = next_target(self.target);
+                self.refresh();
+            },
+            KeyCode::Char('r') if ctrl => {
+                self.mode = self.mode.next();
+                self.refresh();
+            },
+            KeyCode::Char('a') if ctrl => {
+                let enable = !self.entries.iter().all(|entry| entry.enabled);
+                for entry in &mut self.entries {
+                    entry.enabled = enable;
+                }
+                self.refresh();
+            },
+            KeyCode::Tab => {
+                self.focus = match self.focus {
+                    Focus::Pattern => Focus::With,
+                    Focus::With => Focus::Files,
+                    Focus::Files => Focus::Pattern,
+                };
+            },
+            KeyCode::BackTab => {
+                self.focus = match self.focus {
+                    Focus::Pattern => Focus::Files,
+                    Focus::With => Focus::Pattern,
+                    Focus::Files => Focus::With,
+                };
+            },
+            KeyCode::Up => self.select(self.selected.saturating_sub(1)),
+            KeyCode::Down => self.select(self.selected.saturating_add(1)),
+            KeyCode::PageUp => self.select(self.selected.saturating_sub(PAGE)),
+            KeyCode::PageDown => {
+                self.select(self.selected.saturating_add(PAGE));
+            },
+            KeyCode::Home => self.select(0),
+            KeyCode::End => self.select(usize::MAX),
+            KeyCode::Enter if self.confirming => {
+                self.confirming = false;
+                self.apply();
+            },
+            KeyCode::Enter => self.confirm(),
+            KeyCode::Char(ch) if self.focus == Focus::Files && !ctrl => {
+                self.handle_row_key(ch);
+            },
+            KeyCode::Char(ch) if !ctrl => {
+                if let Some(input) = self.input() {
+                    input.push(ch);
+                    self.refresh();
+                }
+            },
+            KeyCode::Backspace => {
+                if let Some(input) = self.input() {
+                    input.pop();
+                    self.refresh();
+                }
+            },
+            KeyCode::Left
+            | KeyCode::Right
+            | KeyCode::Delete
+            | KeyCode::Insert
+            | KeyCode::F(_)
+            | KeyCode::Char(_)
+            | KeyCode::Null
+            | KeyCode::CapsLock
+            | KeyCode::ScrollLock
+            | KeyCode::NumLock
+            | KeyCode::PrintScreen
+            | KeyCode::Pause
+            | KeyCode::Menu
+            | KeyCode::KeypadBegin
+            | KeyCode::Media(_)
+            | KeyCode::Modifier(_) => {},
+        }
+    }
+
+    /// Keys acting on the selected entry.
+    fn handle_row_key(&mut self, key: char) {
+        let global = self.target;
+        let Some(entry) = self.entries.get_mut(self.selected) else {
+            return;
+        };
+        match key {
+            ' ' | 'x' => entry.enabled = !entry.enabled,
+            't' => {
+                // Following the app's target again once it's cycled back
+                let next = next_target(entry.target.unwrap_or(global));
+                entry.target = (next != global).then_some(next);
+            },
+            'u' => {
+                entry.target = None;
+                entry.edited = None;
+            },
+            'e' => {
+                let current = self
+                    .preview
+                    .as_ref()
+                    .ok()
+                    .and_then(|rows| rows.get(self.selected))
+                    .and_then(Row::to)
+                    .and_then(|to| to.file_name())
+                    // Names that aren't valid UTF-8 are edited escaped
+                    .map_or_else(
+                        || entry.file.name().to_string(),
+                        |name| os_bytes::escape(&os_bytes::from_os_str(name)),
+                    );
+                self.editing = Some(current);
+                return;
+            },
+            _ => return,
+        }
+        self.refresh();
+    }
+
+    /// Keys while editing the new name of the selected entry.
+    fn handle_edit_key(&mut self, key: KeyEvent) {
+        let Some(buffer) = self.editing.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.editing = None,
+            KeyCode::Backspace => drop(buffer.pop()),
+            KeyCode::Char(ch) => buffer.push(ch),
+            KeyCode::Enter => {
+                let valid = os_bytes::unescape(buffer).is_some_and(|bytes| {
+                    !matches!(bytes.as_slice(), b"" | b"." | b"..")
+                        && !bytes
+                            .iter()
+                            .any(|&byte| path::is_separator(byte.into()))
+                });
+                if !valid {
+                    self.status = Some(Status::Error(format!(
+                        "{buffer:?} isn't a valid file name."
+                    )));
+                    return;
+                }
+                let name = self.editing.take();
+                if let Some(entry) = self.entries.get_mut(self.selected) {
+                    entry.edited = name;
+                }
+                self.status = None;
+                self.refresh();
+            },
+            KeyCode::Left
+            | KeyCode::Right
+            | KeyCode::Up
+            | KeyCode::Down
+            | KeyCode::Home
+            | KeyCode::End
+            | KeyCode::PageUp
+            | KeyCode::PageDown
+            | KeyCode::Tab
+            | KeyCode::BackTab
+            | KeyCode::Delete
+            | KeyCode::Insert
+            | KeyCode::F(_)
+            | KeyCode::Null
+            | KeyCode::CapsLock
+            | KeyCode::ScrollLock
+            | KeyCode::NumLock
+            | KeyCode::PrintScreen
+            | KeyCode::Pause
+            | KeyCode::Menu
+            | KeyCode::KeypadBegin
+            | KeyCode::Media(_)
+            | KeyCode::Modifier(_) => {},
+        }
+    }
+
+    /// The focused text input, if any.
+    fn input(&mut self) -> Option<&mut String> {
+        match self.focus {
+            Focus::Pattern => Some(&mut self.pattern),
+            Focus::With => Some(&mut self.with),
+            Focus::Files => None,
+        }
+    }
+
+    fn select(&mut self, idx: usize) {
+        self.selected = idx.min(self.entries.len().saturating_sub(1));
+    }
+
+    /// Asks for a second `Enter` before renaming.
+    fn confirm(&mut self) {
+        if let Err(err) = self.preview.as_ref() {
+            self.status = Some(Status::Error(err.clone()));
+            return;
+        }
+        let count = self.renames().len();
+        if count == 0 {
+            self.status = Some(Status::Info("Nothing to do...".to_owned()));
+            return;
+        }
+        self.confirming = true;
+        self.status = Some(Status::Info(format!(
+            "Press Enter again to rename {count} file(s)."
+        )));
+    }
+
+    /// Renames the enabled files, never overriding existing ones.
+    pub fn apply(&mut self) {
+        let plan = self
+            .renames()
+            .into_iter()
+            .map(|(_, rename)| rename)
+            .collect::<Plan>();
+        if !plan.collisions().is_empty() {
+            self.status = Some(Status::Error(
+                "Some renames collide, toggle them off first.".to_owned(),
+            ));
+            return;
+        }
+        let planned = plan.len();
+        let approved = plan.filter_overrides(|_| false);
+
+        let mut done = Vec::new();
+        let mut last_error = None;
+        for result in approved.execute() {
+            match result {
+                Ok(rename) => done.push((rename.from(), rename.to().clone())),
+                Err(err) => last_error = Some(err.to_string()),
+            }
+        }
+
+        // Follow the renames, directories included
+        self.entries = self
+            .entries
+            .drain(..)
+            .filter_map(|entry| {
+                let path =
+                    done.iter().fold(entry.file.path(), |path, renamed| {
+                        match path.strip_prefix(&renamed.0) {
+                            Ok(rest) if rest.as_os_str().is_empty() => {
+                                renamed.1.clone()
+                            },
+                            Ok(rest) => renamed.1.join(rest),
+                            Err(_err) => path,
+                        }
+                    });
+                // Hand edits are done once the entry got renamed
+                let moved = path != entry.file.path();
+                File::from_path(path).ok().map(|file| Entry {
+                    file,
+                    edited: if moved { None } else { entry.edited },
+                    ..entry
+                })
+            })
+            .collect();
+        self.hashed.clear();
+        #[cfg(feature = "media")]
+        self.tagged.clear();
+        self.select(self.selected);
+        self.refresh();
+
+        let skipped = planned.saturating_sub(done.len());
+        self.status = Some(match last_error {
+            Some(err) => Status::Error(format!(
+                "Renamed {} file(s), {skipped} skipped or failed: {err}",
+                done.len()
+            )),
+            None if skipped > 0 => Status::Error(format!(
+                "Renamed {} file(s), {skipped} skipped to avoid overriding.",
+                done.len()
+            )),
+            None => Status::Info(format!("Renamed {} file(s).", done.len())),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{fs, path::Path};
+
+    fn app(dir: &Path, names: &[&str]) -> App {
+        let files = names
+            .iter()
+            .map(|name| {
+                fs::write(dir.join(name), name).unwrap();
+                File::from_path(dir.join(name)).unwrap()
+            })
+            .collect();
+        App::new(
+            files,
+            Mode::String,
+            RenameTarget::Both,
+            String::new(),
+            String::new(),
+        )
+    }
+
+    fn type_str(app: &mut App, text: &str) {
+        for ch in text.chars() {
+            app.handle_key(KeyEvent::from(KeyCode::Char(ch)));
+        }
+    }
+
+    fn new_names(app: &App) -> Vec<Option<String>> {
+        app.preview()
+            .unwrap()
+            .iter()
+            .map(|row| {
+                row.to().map(|to| {
+                    to.file_name().unwrap().to_string_lossy().into_owned()
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_live_preview() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = app(dir.path(), &["a_1.txt", "b_2.txt"]);
+        assert_eq!(new_names(&app), [None, None]);
+
+        type_str(&mut app, "_");
+        app.handle_key(KeyEvent::from(KeyCode::Tab));
+        type_str(&mut app, "-{n}-");
+        assert_eq!(
+            new_names(&app),
+            [Some("a-1-1.txt".to_owned()), Some("b-2-2.txt".to_owned())]
+        );
+        assert_eq!(
+            app.preview().unwrap().first().unwrap().matches(),
+            [Range { start: 1, end: 2 }]
+        );
+
+        app.handle_key(KeyEvent::new(
+            KeyCode::Char('r'),
+            KeyModifiers::CONTROL,
+        ));
+        app.handle_key(KeyEvent::from(KeyCode::Backspace));
+        assert_eq!(app.mode(), Mode::Regex);
+        assert_eq!(app.with(), "-{n}");
+
+        app.handle_key(KeyEvent::from(KeyCode::BackTab));
+        type_str(&mut app, "(");
+        app.preview().unwrap_err();
+    }
+
+    #[test]
+    fn test_files_are_hashed_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = app(dir.path(), &["abc"]);
+        type_str(&mut app, "abc");
+        app.handle_key(KeyEvent::from(KeyCode::Tab));
+        type_str(&mut app, "{hash:md5:4}");
+        // md5 of the content, `abc`
+        assert_eq!(new_names(&app), [Some("9001".to_owned())]);
+
+        // Keystrokes reuse the digests rather than reading the file again
+        fs::write(dir.path().join("abc"), "changed").unwrap();
+        type_str(&mut app, "_");
+        assert_eq!(new_names(&app), [Some("9001_".to_owned())]);
+    }
+
+    #[test]
+    fn test_toggle_and_conflicts() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = app(dir.path(), &["a", "b", "c"]);
+        type_str(&mut app, "b");
+        app.handle_key(KeyEvent::from(KeyCode::Tab));
+        type_str(&mut app, "c");
+        assert_eq!(
+            app.preview().unwrap().get(1).unwrap().conflict(),
+            Some(Conflict::Exists)
+        );
+
+        app.handle_key(KeyEvent::from(KeyCode::Tab));
+        app.handle_key(KeyEvent::from(KeyCode::Down));
+        app.handle_key(KeyEvent::from(KeyCode::Char(' ')));
+        assert!(!app.entries().get(1).unwrap().enabled());
+        assert_eq!(app.renames().len(), 0);
+
+        app.handle_key(KeyEvent::new(
+            KeyCode::Char('a'),
+            KeyModifiers::CONTROL,
+        ));
+        assert!(app.entries().iter().all(Entry::enabled));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_edit_non_utf8_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir
+            .path()
+            .join(os_bytes::into_os_string(b"caf\xe9.txt".to_vec()));
+        fs::write(&path, "").unwrap();
+        let mut app = App::new(
+            vec![File::from_path(&path).unwrap()],
+            Mode::String,
+            RenameTarget::Both,
+            String::new(),
+            String::new(),
+        );
+        app.handle_key(KeyEvent::from(KeyCode::Tab));
+        app.handle_key(KeyEvent::from(KeyCode::Tab));
+
+        // Left as is, not renamed to a lossy name
+        type_str(&mut app, "e");
+        assert_eq!(app.editing(), Some("caf\\xE9.txt"));
+        app.handle_key(KeyEvent::from(KeyCode::Enter));
+        assert_eq!(app.renames().len(), 0);
+
+        type_str(&mut app, "e\\x");
+        app.handle_key(KeyEvent::from(KeyCode::Enter));
+        assert!(matches!(app.status(), Some(&Status::Error(_))));
+        for _ in 0_u8..5 {
+            app.handle_key(KeyEvent::from(KeyCode::Backspace));
+        }
+        type_str(&mut app, "md");
+        app.handle_key(KeyEvent::from(KeyCode::Enter));
+        assert_eq!(
+            app.renames()
+                .into_iter()
+                .next()
+                .map(|(_, rename)| rename.to().clone()),
+            Some(
+                dir.path()
+                    .join(os_bytes::into_os_string(b"caf\xe9.md".to_vec()))
+            )
+        );
+    }
+
+    #[test]
+    fn test_path_target_outside_base() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = app(dir.path(), &["a"]);
+        type_str(&mut app, "a");
+        let ctrl_t = KeyEvent::new(KeyCode::Char('t'), KeyModifiers::CONTROL);
+        while app.target() != RenameTarget::Path {
+            app.handle_key(ctrl_t);
+        }
+
+        // Paths are relative to the working directory, not the temporary one
+        assert!(app.preview().unwrap_err().contains("isn't below"));
+    }
+
+    #[test]
+    fn test_row_edits() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = app(dir.path(), &["a.a", "b.a"]);
+        type_str(&mut app, "a");
+        app.handle_key(KeyEvent::from(KeyCode::Tab));
+        type_str(&mut app, "z");
+        app.handle_key(KeyEvent::from(KeyCode::Tab));
+        assert_eq!(
+            new_names(&app),
+            [Some("z.z".to_owned()), Some("b.z".to_owned())]
+        );
+
+        // Only rename the stem of the first row
+        type_str(&mut app, "t");
+        assert_eq!(
+            app.entries().first().unwrap().target(),
+            Some(RenameTarget::Stem)
+        );
+        assert_eq!(
+            new_names(&app),
+            [Some("z.a".to_owned()), Some("b.z".to_owned())]
+        );
+        type_str(&mut app, "t");
+        assert_eq!(new_names(&app).first().unwrap().as_deref(), Some("a.z"));
+
+        // Hand edit the second row, invalid names are refused
+        app.handle_key(KeyEvent::from(KeyCode::Down));
+        type_str(&mut app, "e");
+        assert_eq!(app.editing(), Some("b.z"));
+        app.handle_key(KeyEvent::from(KeyCode::Backspace));
+        type_str(&mut app, "/");
+        app.handle_key(KeyEvent::from(KeyCode::Enter));
+        assert!(matches!(app.status(), Some(&Status::Error(_))));
+        app.handle_key(KeyEvent::from(KeyCode::Backspace));
+        type_str(&mut app, "txt");
+        app.handle_key(KeyEvent::from(KeyCode::Enter));
+        assert_eq!(app.editing(), None);
+        assert!(app.entries().get(1).unwrap().edited());
+        assert_eq!(
+            new_names(&app),
+            [Some("a.z".to_owned()), Some("b.txt".to_owned())]
+        );
+
+        // Hand edits win over the pattern, until the row is reset
+        app.handle_key(KeyEvent::from(KeyCode::BackTab));
+        type_str(&mut app, "q");
+        assert_eq!(
+            new_names(&app),
+            [Some("a.zq".to_owned()), Some("b.txt".to_owned())]
+        );
+        app.handle_key(KeyEvent::from(KeyCode::Tab));
+        type_str(&mut app, "u");
+        assert!(!app.entries().get(1).unwrap().edited());
+        assert_eq!(
+            new_names(&app),
+            [Some("a.zq".to_owned()), Some("b.zq".to_owned())]
+        );
+    }
+
+    #[test]
+    fn test_apply() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = app(dir.path(), &["a_1", "a_2", "b"]);
+        type_str(&mut app, "a");
+        app.handle_key(KeyEvent::from(KeyCode::Tab));
+        type_str(&mut app, "x");
+        app.handle_key(KeyEvent::from(KeyCode::Tab));
+        app.handle_key(KeyEvent::from(KeyCode::Char(' ')));
+
+        app.handle_key(KeyEvent::from(KeyCode::Enter));
+        assert!(dir.path().join("a_2").exists());
+        app.handle_key(KeyEvent::from(KeyCode::Enter));
+
+        assert!(dir.path().join("a_1").exists());
+        assert!(dir.path().join("x_2").exists());
+        assert_eq!(
+            app.status(),
+            Some(&Status::Info("Renamed 1 file(s).".to_owned()))
+        );
+        assert_eq!(
+            app.entries().get(1).unwrap().file().path(),
+            dir.path().join("x_2")
+        );
+        assert_eq!(new_names(&app), [Some("x_1".to_owned()), None, None]);
+    }
+}
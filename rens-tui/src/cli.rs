@@ -0,0 +1,177 @@
+/* Built-in imports */
+use std::path::PathBuf;
+/* Dependencies */
+use clap::{Parser, ValueEnum, ValueHint};
+use regex::bytes::Regex;
+use rens_common::{
+    options::{
+        git::Options as GitOpt, path_exists, pattern::Options as PatternOpt,
+        recursion::Recursion, selection::Selection,
+    },
+    RenameTarget, SedPattern, Template, COMPOUND_EXTENSIONS,
+};
+
+#[derive(Debug, Parser)]
+#[command(version, about, long_about = None)]
+pub struct Cli {
+    /// Paths to the elements you want to rename.
+    #[arg(
+        required = true,
+        value_parser = path_exists,
+        value_hint = ValueHint::AnyPath,
+    )]
+    pub paths: Vec<PathBuf>,
+
+    /// How the pattern is interpreted, can be changed from the TUI.
+    #[arg(long, short, value_enum, default_value = "string")]
+    pub mode: Mode,
+
+    /// Initial pattern.
+    #[arg(long, short, default_value = "")]
+    pub pattern: String,
+
+    /// Initial replacement.
+    #[arg(long, short, default_value = "")]
+    pub with: String,
+
+    /// Wether to rename the file stem, extension(s), both or the whole path.
+    ///
+    /// Note: filename = <stem>.<extension>, `path` being relative to the
+    /// current directory.
+    #[arg(long, short, default_value = "both", value_enum)]
+    pub target: RenameTarget,
+
+    /// Extensions kept whole when targeting the stem or extension,
+    /// replacing the defaults.
+    #[arg(
+        long = "compound-ext",
+        value_name = "ext",
+        value_delimiter = ',',
+        default_values = COMPOUND_EXTENSIONS,
+    )]
+    pub compound_extensions: Vec<String>,
+
+    #[command(flatten)]
+    pub pattern_opt: PatternOpt,
+
+    #[command(flatten)]
+    pub recursion: Recursion,
+
+    #[command(flatten)]
+    pub git_opt: GitOpt,
+
+    #[command(flatten)]
+    pub selection: Selection,
+}
+
+#[derive(Debug, Default, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub enum Mode {
+    #[default]
+    #[clap(help = "Replace a plain string.")]
+    String,
+    #[clap(help = "Replace matches of a regex.")]
+    Regex,
+    #[clap(help = "Apply a /regex/replacement/flags sed pattern.")]
+    Sed,
+}
+
+impl Mode {
+    pub const fn next(self) -> Self {
+        match self {
+            Self::String => Self::Regex,
+            Self::Regex => Self::Sed,
+            Self::Sed => Self::String,
+        }
+    }
+
+    /// Builds the regex, replacement and limit from the inputs.
+    ///
+    /// Note: sed patterns carry their own replacement and flags, `with` and
+    /// `options` are unused.
+    pub fn parse(
+        self,
+        pattern: &str,
+        with: &str,
+        options: &PatternOpt,
+    ) -> Result<(Regex, Template, usize), String> {
+        let template =
+            || with.parse::<Template>().map_err(|err| err.to_string());
+        match self {
+            Self::String => {
+                let (regex, limit) = options.apply(
+                    Regex::new(&regex::escape(pattern))
+                        .map_err(|err| err.to_string())?,
+                );
+                Ok((regex, template()?, limit))
+            },
+            Self::Regex => {
+                let (regex, limit) = options
+                    .apply(Regex::new(pattern).map_err(|err| err.to_string())?);
+                Ok((regex, template()?, limit))
+            },
+            Self::Sed => pattern
+                .parse::<SedPattern>()
+                .map(SedPattern::export)
+                .map_err(|err| err.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory as _;
+    use rens_common::traits::FileKind;
+
+    #[test]
+    fn verify_conformity() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_shared_options() {
+        let cli = Cli::parse_from([
+            "rens-tui",
+            ".",
+            "--only-dirs",
+            "--type=dir",
+            "--case-insensitive",
+            "--occurence=2",
+            "--compound-ext=tar.gz",
+            "--ignore",
+        ]);
+        assert!(!cli.selection.matches(FileKind::File));
+        assert!(cli.selection.matches(FileKind::Directory));
+        assert!(cli.pattern_opt.case_insensitive);
+        assert_eq!(cli.compound_extensions, ["tar.gz"]);
+        assert!(cli.git_opt.auto_ignore);
+
+        let (regex, _, limit) =
+            Mode::String.parse("a", "b", &cli.pattern_opt).unwrap();
+        assert!(regex.is_match(b"A"));
+        assert_eq!(limit, 2);
+
+        Cli::try_parse_from(["rens-tui", "missing-path"]).unwrap_err();
+    }
+
+    #[test]
+    fn test_mode_parse() {
+        let (regex, with, limit) = Mode::String
+            .parse("a.b", "c", &PatternOpt::default())
+            .unwrap();
+        assert!(!regex.is_match(b"axb"));
+        assert_eq!((with.to_string().as_str(), limit), ("c", 0));
+
+        Mode::Regex
+            .parse("a(", "c", &PatternOpt::default())
+            .unwrap_err();
+        Mode::Regex
+            .parse("a", "{x}", &PatternOpt::default())
+            .unwrap_err();
+
+        let (_, sed_with, sed_limit) = Mode::Sed
+            .parse("/a/b/12", "ignored", &PatternOpt::default())
+            .unwrap();
+        assert_eq!((sed_with.to_string().as_str(), sed_limit), ("b", 12));
+    }
+}
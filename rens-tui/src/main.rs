@@ -1,3 +1,103 @@
-fn main() {
-    println!("Hello, world!");
+/* Modules */
+mod app;
+mod cli;
+mod ui;
+/* Built-in imports */
+use std::{
+    env,
+    io::{self, Stdout},
+    panic,
+    time::SystemTime,
+};
+/* Crate imports */
+use app::{App, Status};
+use cli::Cli;
+/* Dependencies */
+use clap::Parser as _;
+use crossterm::{
+    event::{self, Event, KeyEventKind},
+    execute,
+    terminal::{
+        disable_raw_mode, enable_raw_mode, EnterAlternateScreen,
+        LeaveAlternateScreen,
+    },
+};
+use ratatui::{backend::CrosstermBackend, Terminal};
+use rens_common::{walk::Walker, File};
+
+fn restore_terminal() -> io::Result<()> {
+    disable_raw_mode()?;
+    execute!(io::stdout(), LeaveAlternateScreen)
+}
+
+fn setup_terminal() -> io::Result<Terminal<CrosstermBackend<Stdout>>> {
+    // Leave the alternate screen before printing panics
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        drop(restore_terminal());
+        hook(info);
+    }));
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen)?;
+    Terminal::new(CrosstermBackend::new(io::stdout()))
+}
+
+fn run(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    app: &mut App,
+) -> io::Result<()> {
+    while !app.should_quit() {
+        terminal.draw(|frame| ui::draw(frame, app))?;
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press {
+                app.handle_key(key);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+
+    let filter = cli.selection.filter(&env::current_dir()?)?;
+    let predicates = cli.selection.predicates(SystemTime::now())?;
+    let mut errors = Vec::new();
+    let mut files = Walker::new(
+        cli.recursion.recursive,
+        cli.recursion.depth,
+        cli.recursion.allow_hidden,
+        cli.git_opt.auto_ignore,
+    )
+    .walk_filtered(cli.paths.clone(), filter)
+    .filter_map(|path| {
+        path.map_err(|err| err.to_string())
+            .and_then(|found| {
+                let canonical = dunce::canonicalize(found)
+                    .map_err(|err| err.to_string())?;
+                File::from_path(canonical).map_err(|err| err.to_string())
+            })
+            .map_err(|err| errors.push(err))
+            .ok()
+    })
+    .filter(|file| cli.selection.matches(file.kind()))
+    .filter(|file| predicates.matches(file))
+    .collect::<Vec<_>>();
+    files.sort_by_key(File::path);
+
+    let mut app =
+        App::new(files, cli.mode, cli.target, cli.pattern.clone(), cli.with)
+            .with_pattern_options(cli.pattern_opt)
+            .with_compound_extensions(cli.compound_extensions);
+    if let Some(err) = errors.first() {
+        app.set_status(Status::Error(format!(
+            "Skipped {} path(s): {err}",
+            errors.len()
+        )));
+    }
+
+    let mut terminal = setup_terminal()?;
+    let result = run(&mut terminal, &mut app);
+    restore_terminal()?;
+    Ok(result?)
 }
@@ -0,0 +1,245 @@
+/* Built-in imports */
+use core::ops::Range;
+/* Crate imports */
+use crate::app::{App, Focus, Row, Status};
+/* Dependencies */
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style, Stylize as _},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Row as TableRow, Table, TableState},
+    Frame,
+};
+use rens_common::{os_bytes, plan::Conflict};
+
+const HELP: &str = "Tab focus | ^A toggle all | ^R mode | ^T target | \
+                    Enter apply | Esc quit";
+const FILES_HELP: &str =
+    "Space toggle | e edit | t row target | u reset row | \
+                          Tab focus | Enter apply | Esc quit";
+const EDIT_HELP: &str = "Enter save | Esc cancel";
+
+pub fn draw(frame: &mut Frame, app: &App) {
+    let [inputs, files, footer] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Min(3),
+        Constraint::Length(2),
+    ])
+    .areas(frame.size());
+    let [pattern, with] =
+        Layout::horizontal([Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)])
+            .areas(inputs);
+
+    draw_input(
+        frame,
+        pattern,
+        &format!("Pattern ({:?})", app.mode()),
+        app.pattern(),
+        app.focus() == Focus::Pattern,
+    );
+    draw_input(
+        frame,
+        with,
+        "Replacement",
+        app.with(),
+        app.focus() == Focus::With,
+    );
+    draw_files(frame, files, app);
+    draw_footer(frame, footer, app);
+}
+
+const fn border(focused: bool) -> Style {
+    if focused {
+        Style::new().fg(Color::Yellow)
+    } else {
+        Style::new()
+    }
+}
+
+fn draw_input(
+    frame: &mut Frame,
+    area: Rect,
+    title: &str,
+    text: &str,
+    focused: bool,
+) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(border(focused))
+        .title(title.to_owned());
+    frame.render_widget(Paragraph::new(text.to_owned()).block(block), area);
+    if focused {
+        let width = u16::try_from(text.chars().count()).unwrap_or(u16::MAX);
+        frame.set_cursor(
+            area.x
+                .saturating_add(1)
+                .saturating_add(width)
+                .min(area.right().saturating_sub(2)),
+            area.y.saturating_add(1),
+        );
+    }
+}
+
+/// Splits `name` so the `matches` get highlighted.
+///
+/// Note: `matches` are byte ranges, segments are escaped once split.
+fn highlighted(name: &[u8], matches: &[Range<usize>]) -> Line<'static> {
+    let mut spans = Vec::new();
+    let mut last = 0;
+    for range in matches {
+        if let (Some(before), Some(found)) =
+            (name.get(last..range.start), name.get(range.clone()))
+        {
+            spans.push(Span::raw(os_bytes::escape(before)));
+            spans.push(Span::styled(
+                os_bytes::escape(found),
+                Style::new().fg(Color::Black).bg(Color::Yellow),
+            ));
+            last = range.end;
+        }
+    }
+    spans.push(Span::raw(os_bytes::escape(
+        name.get(last..).unwrap_or_default(),
+    )));
+    Line::from(spans)
+}
+
+fn new_name(row: &Row, edited: bool) -> Line<'static> {
+    let Some(to) = row.to() else {
+        return Line::from("(unchanged)".dark_gray());
+    };
+    let name = to
+        .file_name()
+        .map(|name| os_bytes::escape(&os_bytes::from_os_str(name)))
+        .unwrap_or_default();
+    match row.conflict() {
+        Some(Conflict::Exists) => {
+            Line::from(vec![name.red(), " (exists)".red()])
+        },
+        Some(Conflict::Collision) => {
+            Line::from(vec![name.red(), " (collision)".red()])
+        },
+        Some(_) => Line::from(name.red()),
+        None if edited => Line::from(name.cyan()),
+        None => Line::from(name.green()),
+    }
+}
+
+fn draw_files(frame: &mut Frame, area: Rect, app: &App) {
+    let empty = Row::default();
+    let rows = app.entries().iter().enumerate().map(|(idx, entry)| {
+        let row = app
+            .preview()
+            .ok()
+            .and_then(|rows| rows.get(idx))
+            .unwrap_or(&empty);
+        let old = highlighted(&entry.file().name().to_bytes(), row.matches());
+        let style = if entry.enabled() {
+            Style::new()
+        } else {
+            Style::new().add_modifier(Modifier::DIM | Modifier::CROSSED_OUT)
+        };
+        let new = match app.editing() {
+            Some(buffer) if idx == app.selected() => Line::from(
+                format!("{buffer}_")
+                    .yellow()
+                    .add_modifier(Modifier::UNDERLINED),
+            ),
+            Some(_) | None => new_name(row, entry.edited()),
+        };
+        TableRow::new([
+            Line::from(if entry.enabled() { "[x]" } else { "[ ]" }),
+            Line::from(
+                entry
+                    .target()
+                    .map(|target| target.to_string())
+                    .unwrap_or_default(),
+            ),
+            old,
+            new,
+        ])
+        .style(style)
+    });
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(3),
+            Constraint::Length(9),
+            Constraint::Ratio(1, 2),
+            Constraint::Ratio(1, 2),
+        ],
+    )
+    .header(TableRow::new(["", "Target", "Name", "New name"]).bold())
+    .highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(border(app.focus() == Focus::Files))
+            .title(format!("Files (target: {})", app.target())),
+    );
+    let mut state = TableState::default().with_selected(Some(app.selected()));
+    frame.render_stateful_widget(table, area, &mut state);
+}
+
+fn draw_footer(frame: &mut Frame, area: Rect, app: &App) {
+    let invalid = app.preview().err().cloned();
+    let status = match (invalid, app.status().cloned()) {
+        (Some(err), _) | (None, Some(Status::Error(err))) => {
+            Line::from(err.red())
+        },
+        (None, Some(Status::Info(info))) => Line::from(info),
+        (None, None) => Line::default(),
+    };
+    let help = if app.editing().is_some() {
+        EDIT_HELP
+    } else if app.focus() == Focus::Files {
+        FILES_HELP
+    } else {
+        HELP
+    };
+    frame.render_widget(
+        Paragraph::new(vec![status, Line::from(help.dark_gray())]),
+        area,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::Mode;
+    use ratatui::{backend::TestBackend, buffer::Cell, Terminal};
+    use rens_common::{File, RenameTarget};
+    use std::fs;
+
+    #[test]
+    fn test_draw() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("old.txt"), "").unwrap();
+        fs::write(dir.path().join("new.txt"), "").unwrap();
+        let files = ["new.txt", "old.txt"]
+            .map(|name| File::from_path(dir.path().join(name)).unwrap());
+        let app = App::new(
+            files.into(),
+            Mode::String,
+            RenameTarget::Both,
+            "old".to_owned(),
+            "new".to_owned(),
+        );
+
+        let mut terminal = Terminal::new(TestBackend::new(80, 12)).unwrap();
+        terminal.draw(|frame| draw(frame, &app)).unwrap();
+        let screen = terminal
+            .backend()
+            .buffer()
+            .content()
+            .chunks(80)
+            .map(|line| line.iter().map(Cell::symbol).collect())
+            .collect::<Vec<String>>();
+
+        let mut lines = screen.iter();
+        assert!(lines.nth(1).unwrap().contains("\u{2502}old"));
+        assert!(lines.nth(3).unwrap().contains("(unchanged)"));
+        assert!(lines.next().unwrap().contains("new.txt (exists)"));
+    }
+}